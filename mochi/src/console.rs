@@ -0,0 +1,383 @@
+//! Where shell output goes: the firmware text console, or a wasabi
+//! [`Terminal`] on the GOP framebuffer when `console=gop` is set in
+//! `\qubit.conf`. The GOP terminal looks and colors the same on every machine,
+//! which helps where the firmware text mode is small or monochrome.
+//!
+//! Commands write their normal output through a [`Sink`], which `>`/`>>`
+//! redirection points at a buffer; there cursor or color changes are ignored.
+//! Errors and prompts always go to [`stdout`] so they stay on screen.
+//!
+//! Everything that reaches the screen is also kept, line by line, in a
+//! scrollback buffer that [`scroll`] pages through, and copied to COM1.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write as _};
+use core::ops::{Deref, DerefMut};
+use shared::color::Rgb;
+use shared::config::SCROLLBACK_MAX;
+use shared::ring::RingBuffer;
+use shared::{serial, vga};
+use spin::{Mutex, MutexGuard};
+use uefi::prelude::*;
+use uefi::proto::console::text::{Color, Output};
+use wasabi::Terminal;
+
+/// Like `shared::kprintln!`, but honours the active console.
+macro_rules! kprintln {
+    ($st:expr, $($arg:tt)*) => {{
+        let mut out = $crate::console::stdout($st);
+        let _ = core::fmt::Write::write_fmt(&mut out, core::format_args!($($arg)*));
+        let _ = core::fmt::Write::write_str(&mut out, "\n");
+    }};
+}
+pub(crate) use kprintln;
+
+/// Like `kprintln!`, but to a command's [`Sink`] instead of the console.
+macro_rules! outln {
+    ($st:expr, $sink:expr, $($arg:tt)*) => {{
+        let mut out = $crate::console::out($st, $sink);
+        let _ = core::fmt::Write::write_fmt(&mut out, core::format_args!($($arg)*));
+        let _ = core::fmt::Write::write_str(&mut out, "\n");
+    }};
+}
+pub(crate) use outln;
+
+static TERMINAL: Mutex<Option<Terminal>> = Mutex::new(None);
+
+/// Sends all later output to a framebuffer terminal. Fails without GOP.
+pub fn use_gop(st: &mut SystemTable<Boot>) -> uefi::Result {
+    let term = wasabi::with_gop(st.boot_services(), |gop| {
+        Terminal::new(gop, &wasabi::font::DEFAULT)
+    })?;
+    *TERMINAL.lock() = Some(term);
+    Ok(())
+}
+
+/// The GOP terminal, locked for as long as an [`Out`] writes to it.
+pub struct GopTerm(MutexGuard<'static, Option<Terminal>>);
+
+impl Deref for GopTerm {
+    type Target = Terminal;
+
+    fn deref(&self) -> &Terminal {
+        // `stdout` only makes one after checking a terminal is set.
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for GopTerm {
+    fn deref_mut(&mut self) -> &mut Terminal {
+        self.0.as_mut().unwrap()
+    }
+}
+
+static THEME: Mutex<(Rgb, Rgb)> = Mutex::new((Rgb::LIGHT_GRAY, Rgb::BLACK));
+
+/// Records the normal text colors, which commands go back to after
+/// highlighting something.
+pub fn set_theme(fg: Rgb, bg: Rgb) {
+    *THEME.lock() = (fg, bg);
+}
+
+/// The normal `(foreground, background)` text colors.
+pub fn theme() -> (Rgb, Rgb) {
+    *THEME.lock()
+}
+
+/// Where a command's normal output goes.
+pub enum Sink {
+    Console,
+    /// Collected for `>`/`>>` redirection.
+    Capture(String),
+}
+
+impl Sink {
+    /// Whether output lands on the screen, so colors are worth setting.
+    pub fn is_console(&self) -> bool {
+        matches!(self, Sink::Console)
+    }
+}
+
+/// Screen lines already written, oldest first, plus the line in progress.
+struct Scrollback {
+    lines: RingBuffer<String, { SCROLLBACK_MAX as usize }>,
+    current: String,
+    /// Column of the cursor within `current`, in chars.
+    col: usize,
+    cap: usize,
+    /// How many lines the view is scrolled back; 0 is the live screen.
+    offset: usize,
+    /// Set while [`scroll`] repaints so the repaint isn't recorded again.
+    paused: bool,
+}
+
+static SCROLLBACK: Mutex<Scrollback> = Mutex::new(Scrollback {
+    lines: RingBuffer::new(),
+    current: String::new(),
+    col: 0,
+    cap: 0,
+    offset: 0,
+    paused: false,
+});
+
+impl Scrollback {
+    /// Follows `s` the way a terminal would: `\r` and backspace move the
+    /// cursor, other characters overwrite, and `\n` finishes the line.
+    fn record(&mut self, s: &str) {
+        if self.paused || self.cap == 0 {
+            return;
+        }
+        for c in s.chars() {
+            match c {
+                '\n' => {
+                    let line = core::mem::take(&mut self.current);
+                    self.lines.push(String::from(line.trim_end()));
+                    self.col = 0;
+                    self.trim();
+                }
+                '\r' => self.col = 0,
+                '\u{8}' => self.col = self.col.saturating_sub(1),
+                _ => {
+                    match self.current.char_indices().nth(self.col) {
+                        Some((at, old)) => self
+                            .current
+                            .replace_range(at..at + old.len_utf8(), c.encode_utf8(&mut [0; 4])),
+                        None => self.current.push(c),
+                    }
+                    self.col += 1;
+                }
+            }
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.lines.len() > self.cap {
+            self.lines.pop_oldest();
+        }
+    }
+}
+
+/// Keeps up to `lines` lines (at most [`SCROLLBACK_MAX`]) for [`scroll`];
+/// 0 turns scrollback off.
+pub fn set_scrollback(lines: usize) {
+    let mut sb = SCROLLBACK.lock();
+    sb.cap = lines.min(SCROLLBACK_MAX as usize);
+    sb.trim();
+}
+
+/// Whether [`scroll`] has moved the view away from the live screen.
+pub fn is_scrolled() -> bool {
+    SCROLLBACK.lock().offset > 0
+}
+
+/// Moves the view `pages` screens back (negative: forward) through the
+/// scrollback. Coming back to the newest lines repaints the live screen with
+/// the cursor where it was, so an interrupted line edit carries on.
+pub fn scroll(st: &mut SystemTable<Boot>, pages: isize) {
+    let (_, rows) = stdout(st).size();
+    let page = rows.saturating_sub(1).max(1);
+    let (old, offset) = {
+        let sb = SCROLLBACK.lock();
+        // The line in progress counts as the last line.
+        let max = (sb.lines.len() + 1).saturating_sub(page);
+        let offset = sb
+            .offset
+            .saturating_add_signed(pages.saturating_mul(page as isize))
+            .min(max);
+        (sb.offset, offset)
+    };
+    if offset != old {
+        show(st, offset);
+    }
+}
+
+/// Returns to the live screen if the view is scrolled back.
+pub fn end_scroll(st: &mut SystemTable<Boot>) {
+    if is_scrolled() {
+        show(st, 0);
+    }
+}
+
+/// Repaints the screen with the page ending `offset` lines before the newest.
+fn show(st: &mut SystemTable<Boot>, offset: usize) {
+    let (cols, rows) = stdout(st).size();
+    let page = rows.saturating_sub(1).max(1);
+    let mut view: Vec<String> = Vec::new();
+    let back = {
+        let mut sb = SCROLLBACK.lock();
+        sb.offset = offset;
+        let total = sb.lines.len() + 1;
+        let end = total - offset;
+        let lines = sb.lines.iter().chain([&sb.current]);
+        for line in lines.take(end).skip(end.saturating_sub(page)) {
+            view.push(line.chars().take(cols.saturating_sub(1)).collect());
+        }
+        sb.paused = true;
+        sb.current.chars().count().saturating_sub(sb.col)
+    };
+
+    {
+        let mut out = stdout(st);
+        let _ = out.clear();
+        for (i, line) in view.iter().enumerate() {
+            if i > 0 {
+                let _ = out.write_char('\n');
+            }
+            let _ = out.write_str(line);
+        }
+        if offset > 0 {
+            let _ = write!(out, "\n-- scrollback: {} lines up (PgUp/PgDn) --", offset);
+        } else {
+            for _ in 0..back {
+                let _ = out.write_char('\u{8}');
+            }
+        }
+    }
+    SCROLLBACK.lock().paused = false;
+}
+
+/// The active console, in place of `st.stdout()`. GOP output is flushed to
+/// the screen when the handle is dropped.
+pub fn stdout(st: &mut SystemTable<Boot>) -> Out<'_> {
+    let term = TERMINAL.lock();
+    if term.is_some() {
+        Out::Gop(GopTerm(term), st.boot_services())
+    } else {
+        Out::Text(st.stdout())
+    }
+}
+
+/// A command's output: `sink` if it is capturing, otherwise the console.
+pub fn out<'a>(st: &'a mut SystemTable<Boot>, sink: &'a mut Sink) -> Out<'a> {
+    match sink {
+        Sink::Capture(buf) => Out::Capture(buf),
+        Sink::Console => stdout(st),
+    }
+}
+
+pub enum Out<'a> {
+    Text(&'a mut Output),
+    Gop(GopTerm, &'a BootServices),
+    Capture(&'a mut String),
+}
+
+impl Out<'_> {
+    pub fn reset(&mut self, extended: bool) -> uefi::Result {
+        match self {
+            Out::Text(out) => out.reset(extended),
+            Out::Gop(term, _) => {
+                term.reset_colors();
+                term.clear();
+                Ok(())
+            }
+            Out::Capture(_) => Ok(()),
+        }
+    }
+
+    pub fn clear(&mut self) -> uefi::Result {
+        match self {
+            Out::Text(out) => out.clear(),
+            Out::Gop(term, _) => {
+                term.clear();
+                Ok(())
+            }
+            Out::Capture(_) => Ok(()),
+        }
+    }
+
+    pub fn enable_cursor(&mut self, visible: bool) -> uefi::Result {
+        match self {
+            Out::Text(out) => out.enable_cursor(visible),
+            Out::Gop(term, _) => {
+                term.set_cursor_visible(visible);
+                Ok(())
+            }
+            Out::Capture(_) => Ok(()),
+        }
+    }
+
+    pub fn set_color(&mut self, fg: Rgb, bg: Rgb) -> uefi::Result {
+        match self {
+            Out::Text(out) => out.set_color(uefi_color(fg.to_vga()), uefi_color(bg.to_vga())),
+            Out::Gop(term, _) => {
+                term.set_colors(fg, bg);
+                Ok(())
+            }
+            Out::Capture(_) => Ok(()),
+        }
+    }
+
+    /// Columns and rows of the screen, or 80x25 when the firmware won't say.
+    pub fn size(&mut self) -> (usize, usize) {
+        match self {
+            Out::Text(out) => match out.current_mode() {
+                Ok(Some(mode)) => (mode.columns(), mode.rows()),
+                _ => (80, 25),
+            },
+            Out::Gop(term, _) => (term.cols(), term.rows()),
+            Out::Capture(_) => (80, 25),
+        }
+    }
+
+    pub fn set_cursor_position(&mut self, column: usize, row: usize) -> uefi::Result {
+        match self {
+            Out::Text(out) => out.set_cursor_position(column, row),
+            Out::Gop(term, _) => {
+                term.set_cursor(column, row);
+                Ok(())
+            }
+            Out::Capture(_) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Write for Out<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self {
+            Out::Text(out) => {
+                SCROLLBACK.lock().record(s);
+                serial::write_str(s);
+                out.write_str(s)
+            }
+            Out::Gop(term, _) => {
+                SCROLLBACK.lock().record(s);
+                serial::write_str(s);
+                term.write_str(s)
+            }
+            Out::Capture(buf) => {
+                buf.push_str(s);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for Out<'_> {
+    fn drop(&mut self) {
+        if let Out::Gop(term, bs) = self {
+            let _ = wasabi::with_gop(bs, |gop| term.flush(gop));
+        }
+    }
+}
+
+fn uefi_color(c: vga::Color) -> Color {
+    match c {
+        vga::Color::Black => Color::Black,
+        vga::Color::Blue => Color::Blue,
+        vga::Color::Green => Color::Green,
+        vga::Color::Cyan => Color::Cyan,
+        vga::Color::Red => Color::Red,
+        vga::Color::Magenta => Color::Magenta,
+        vga::Color::Brown => Color::Brown,
+        vga::Color::LightGray => Color::LightGray,
+        vga::Color::DarkGray => Color::DarkGray,
+        vga::Color::LightBlue => Color::LightBlue,
+        vga::Color::LightGreen => Color::LightGreen,
+        vga::Color::LightCyan => Color::LightCyan,
+        vga::Color::LightRed => Color::LightRed,
+        vga::Color::LightMagenta => Color::LightMagenta,
+        vga::Color::Yellow => Color::Yellow,
+        vga::Color::White => Color::White,
+    }
+}
@@ -0,0 +1,115 @@
+use uefi::prelude::*;
+use uefi::proto::console::text::{Key, ScanCode};
+
+/// A special key arriving again within this window counts as being held.
+const REPEAT_WINDOW_MS: u64 = 150;
+const CTRL_C: char = '\u{3}';
+
+/// Polls the firmware keyboard and keeps just enough timing state to notice
+/// held keys. Firmware auto-repeat is inconsistent, so a held arrow may come in
+/// as a slow trickle of identical events; `step()` turns a streak of them into a
+/// larger movement.
+pub struct KeyReader {
+    clock_ms: u64,
+    last_special: Option<(ScanCode, u64)>,
+    streak: u32,
+}
+
+impl KeyReader {
+    pub fn new() -> Self {
+        Self {
+            clock_ms: 0,
+            last_special: None,
+            streak: 0,
+        }
+    }
+
+    /// Returns the next key if one is pending, otherwise stalls briefly.
+    pub fn poll(&mut self, st: &mut SystemTable<Boot>) -> Option<Key> {
+        let read_result = { st.stdin().read_key() };
+        match read_result {
+            Ok(Some(key)) => {
+                self.track(key);
+                Some(key)
+            }
+            Ok(None) => {
+                st.boot_services().stall(1_000);
+                self.clock_ms += 1;
+                None
+            }
+            Err(_) => {
+                st.boot_services().stall(2_000);
+                self.clock_ms += 2;
+                None
+            }
+        }
+    }
+
+    pub fn read(&mut self, st: &mut SystemTable<Boot>) -> Key {
+        loop {
+            if let Some(key) = self.poll(st) {
+                return key;
+            }
+        }
+    }
+
+    /// Like `read`, but gives up after roughly `timeout_ms` without a key.
+    pub fn read_timeout(&mut self, st: &mut SystemTable<Boot>, timeout_ms: u64) -> Option<Key> {
+        let deadline = self.clock_ms + timeout_ms;
+        while self.clock_ms < deadline {
+            if let Some(key) = self.poll(st) {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    /// Stalls for roughly `ms`, giving up early with `false` if Ctrl-C is
+    /// pressed. Other keys are swallowed.
+    pub fn sleep(&mut self, st: &mut SystemTable<Boot>, ms: u64) -> bool {
+        let deadline = self.clock_ms + ms;
+        while self.clock_ms < deadline {
+            if let Some(Key::Printable(c)) = self.poll(st) {
+                if char::from(c) == CTRL_C {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Whether Ctrl-C is waiting, checked without stalling. Any other key
+    /// read on the way is dropped.
+    pub fn ctrl_c(&mut self, st: &mut SystemTable<Boot>) -> bool {
+        matches!(st.stdin().read_key(), Ok(Some(Key::Printable(c))) if char::from(c) == CTRL_C)
+    }
+
+    /// How many repeats of the current special key have been seen back to back.
+    pub fn held(&self) -> u32 {
+        self.streak
+    }
+
+    /// Movement multiplier for the last special key, growing while it is held.
+    pub fn step(&self) -> usize {
+        match self.streak {
+            0..=2 => 1,
+            3..=8 => 2,
+            _ => 4,
+        }
+    }
+
+    fn track(&mut self, key: Key) {
+        let Key::Special(sc) = key else {
+            self.last_special = None;
+            self.streak = 0;
+            return;
+        };
+        self.streak = match self.last_special {
+            Some((prev, at)) if prev == sc && self.clock_ms - at <= REPEAT_WINDOW_MS => {
+                self.streak + 1
+            }
+            _ => 0,
+        };
+        self.last_special = Some((sc, self.clock_ms));
+    }
+}
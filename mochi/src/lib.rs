@@ -1,8 +1,12 @@
 #![no_std]
 extern crate alloc;
 
+mod console;
+mod input;
 mod tui;
 
+pub use tui::program_names;
+
 use log::info;
 use shared::store::{COMP, NAME};
 use uefi::prelude::*;
@@ -10,6 +14,9 @@ use uefi::prelude::*;
 #[no_mangle]
 pub extern "C" fn kmain(_image_handle: Handle, mut system_table: SystemTable<Boot>) -> ! {
     uefi::helpers::init(&mut system_table).unwrap();
+    if let Ok(now) = system_table.runtime_services().get_time() {
+        shared::store::set_boot_time(tui::epoch_secs(&now).max(1) as u64);
+    }
 
     info!(">>> {NAME} Stage 1 - Initializing <<<");
 
@@ -1,17 +1,56 @@
+use crate::console::{self, kprintln, outln, Sink};
+use crate::input::KeyReader;
 use core::fmt::Write as _;
 use heapless::Vec;
-use shared::kprintln;
-use shared::store::{COMP, NAME, PREFIX, VERSION};
+use nori::NoriError;
+use shared::color::{self, Rgb};
+use shared::config::{self, Config};
+use shared::log;
+use shared::path::join_path;
+use shared::pci;
+use shared::ring::RingBuffer;
+use shared::store::{self, PREFIX};
+use shared::text::edit_distance;
+use shared::vga;
 use uefi::prelude::*;
 use uefi::proto::console::text::{Input, Key, ScanCode};
-use uefi::proto::media::file::{File, FileAttribute, FileMode, FileType};
 use uefi::proto::media::fs::SimpleFileSystem;
-use uefi::table::boot::SearchType;
+use uefi::table::boot::{MemoryType, SearchType};
+use uefi::table::runtime::{Daylight, ResetType};
 use uefi::Identify;
+use wasabi::font::Font as _;
 
 struct ProgramEntry {
     name: &'static str,
-    run: fn(st: &mut SystemTable<Boot>),
+    /// Called with the shell's working directory, for resolving relative paths.
+    run: fn(st: &mut SystemTable<Boot>, sink: &mut Sink, cwd: &str, args: &str) -> ProgramResult,
+}
+
+/// How a program handed control back to the shell.
+enum ProgramResult {
+    /// Ran to completion.
+    Ok,
+    /// Stopped early because something went wrong.
+    Error(heapless::String<128>),
+    /// The user asked to leave (ESC, `exit`, `:q`, Ctrl+D, ...).
+    Exit,
+}
+
+impl ProgramResult {
+    /// Whether this counts as success for `if` and `$?`. Quitting is not a failure.
+    fn is_success(&self) -> bool {
+        !matches!(self, ProgramResult::Error(_))
+    }
+
+    fn error(message: &str) -> Self {
+        let mut s = heapless::String::new();
+        for c in message.chars() {
+            if s.push(c).is_err() {
+                break;
+            }
+        }
+        ProgramResult::Error(s)
+    }
 }
 
 const PROGRAMS: &[ProgramEntry] = &[
@@ -31,8 +70,133 @@ const PROGRAMS: &[ProgramEntry] = &[
         name: "zam",
         run: zam_program,
     },
+    ProgramEntry {
+        name: "plasma",
+        run: plasma_program,
+    },
+    ProgramEntry {
+        name: "hexdump",
+        run: hexdump_program,
+    },
 ];
 
+/// `ls` highlights entries modified at most this many seconds ago.
+const RECENT_SECS: i64 = 60;
+const RECENT_FG: Rgb = Rgb::LIGHT_GREEN;
+/// `ls -l` colors directories with this.
+const DIR_FG: Rgb = Rgb::LIGHT_BLUE;
+
+/// One row of an `ls` listing.
+struct LsEntry {
+    name: heapless::String<64>,
+    is_dir: bool,
+    size: u64,
+    recent: bool,
+}
+
+/// Number of decimal digits in `n`, for right-aligning sizes.
+fn decimal_width(mut n: u64) -> usize {
+    let mut width = 1;
+    while n >= 10 {
+        n /= 10;
+        width += 1;
+    }
+    width
+}
+
+const HISTORY_CAP: usize = 32;
+type History = RingBuffer<heapless::String<256>, HISTORY_CAP>;
+/// Accepted lines are appended here and read back at startup. Only the last
+/// `HISTORY_CAP` survive a load; the file is rewritten if it had more.
+const HISTORY_PATH: &str = "\\.mochi_history";
+
+const VARS_CAP: usize = 32;
+/// Shell variables set with `name=value`, in assignment order.
+type Vars = Vec<(heapless::String<32>, heapless::String<128>), VARS_CAP>;
+
+/// How deep `\bin` scripts may call other scripts.
+const MAX_SCRIPT_DEPTH: usize = 8;
+
+/// State carried between shell lines.
+struct Shell {
+    cwd: heapless::String<256>,
+    oldpwd: Option<heapless::String<256>>,
+    vars: Vars,
+    /// `alias` shortcuts, name to replacement for the command word.
+    aliases: Vars,
+    /// Whether the last command succeeded; `$?` expands to 0 or 1.
+    last_ok: bool,
+    history: History,
+    /// Lines ever added to `history`; the newest entry is number `history_count`.
+    history_count: usize,
+    /// Settings from `\qubit.conf`; `config set` keeps this and the file in step.
+    config: Config,
+    /// How many `\bin` scripts are running, innermost last.
+    script_depth: usize,
+}
+
+impl Shell {
+    fn new() -> Self {
+        let mut cwd = heapless::String::new();
+        let _ = cwd.push('/');
+        Self {
+            cwd,
+            oldpwd: None,
+            vars: Vec::new(),
+            aliases: Vec::new(),
+            last_ok: true,
+            history: History::new(),
+            history_count: 0,
+            config: Config::default(),
+            script_depth: 0,
+        }
+    }
+
+    /// Appends `line` to the history unless it repeats the newest entry.
+    fn remember(&mut self, line: &str) {
+        if self.history.newest().map(|h| h.as_str()) == Some(line) {
+            return;
+        }
+        let mut item = heapless::String::new();
+        let _ = item.push_str(line);
+        self.history.push(item);
+        self.history_count += 1;
+    }
+
+    /// History entry number `n` (1-based, as shown by `history`), if still retained.
+    fn history_entry(&self, n: usize) -> Option<&str> {
+        if n == 0 {
+            return None;
+        }
+        let age = self.history_count.checked_sub(n)?;
+        self.history.get(age).map(|h| h.as_str())
+    }
+
+    /// The `ps1` setting with `\w` replaced by the current directory.
+    fn prompt(&self) -> heapless::String<256> {
+        let mut out = heapless::String::new();
+        let mut parts = self.config.ps1.split("\\w");
+        if let Some(first) = parts.next() {
+            let _ = out.push_str(first);
+        }
+        for part in parts {
+            let _ = out.push_str(&self.cwd);
+            let _ = out.push_str(part);
+        }
+        out
+    }
+}
+
+/// Ctrl+D. Ends input on an empty line; ignored otherwise.
+const EOT: char = '\u{4}';
+/// Ctrl+C. Abandons the line being typed.
+const CTRL_C: char = '\u{3}';
+/// Ctrl+R. Searches history backwards.
+const CTRL_R: char = '\u{12}';
+/// Ctrl+A and Ctrl+E jump to the start and end of the line.
+const CTRL_A: char = '\u{1}';
+const CTRL_E: char = '\u{5}';
+
 const COMMAND_NAMES: &[&str] = &[
     "help",
     "clear",
@@ -40,19 +204,92 @@ const COMMAND_NAMES: &[&str] = &[
     "run",
     "ls",
     "pwd",
+    "cd",
     "fs-handles",
+    "df",
     "cat",
+    "hexdump",
+    "grep",
+    "wc",
+    "head",
+    "tail",
+    "cp",
+    "mv",
+    "rm",
+    "exists",
+    "alias",
+    "unalias",
+    "history",
+    "config",
+    "theme",
+    "watch",
+    "seq",
+    "printf",
+    "od",
+    "mem",
+    "memmap",
+    "lspci",
+    "sleep",
+    "date",
+    "uptime",
+    "version",
+    "reboot",
+    "shutdown",
+    "exit",
     "x:debug-panic",
 ];
 
+/// Flags each command accepts, offered by tab completion after a `-`.
+const COMMAND_FLAGS: &[(&str, &[&str])] = &[
+    ("cp", &["-r", "-m"]),
+    ("grep", &["-i"]),
+    ("head", &["-n"]),
+    ("ls", &["-R", "-l"]),
+    ("memmap", &["-s"]),
+    ("od", &["-A", "-t"]),
+    ("tail", &["-n"]),
+];
+
+/// Commands whose argument Tab completes against directory entries.
+const PATH_COMMANDS: &[&str] = &["cat", "hexdump", "grep", "wc", "head", "tail", "ls", "cd"];
+
+/// Longest `sleep` accepted, one day.
+const SLEEP_MAX_SECS: u64 = 24 * 60 * 60;
+
+/// Most `;`-separated commands one shell line may hold.
+const MAX_LINE_COMMANDS: usize = 16;
+
+/// Most numbers one `seq` prints, so a typo can't fill the heap under `>`.
+const SEQ_MAX_COUNT: i64 = 100_000;
+
+/// How far `ls -R` descends below the directory it lists.
+const LS_MAX_DEPTH: usize = 8;
+
 pub fn run(st: &mut SystemTable<Boot>) -> ! {
+    let mut shell = Shell::new();
+    if let Ok(bytes) =
+        nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, config::PATH))
     {
-        let stdout = st.stdout();
-        let _ = stdout.reset(false);
-        let _ = stdout.enable_cursor(true);
-        let _ = stdout.clear();
-        kprintln!(st, "{COMP} {NAME} {VERSION} tty0");
+        shell.config = Config::parse(core::str::from_utf8(&bytes).unwrap_or(""));
+    }
+    load_history(st, &mut shell);
+    console::set_scrollback(shell.config.scrollback as usize);
+    let gop_failed = shell.config.console == "gop" && console::use_gop(st).is_err();
+
+    {
+        {
+            let mut stdout = console::stdout(st);
+            let _ = stdout.reset(false);
+            let _ = stdout.enable_cursor(true);
+        }
+        apply_theme(st, &shell.config);
+        let _ = console::stdout(st).clear();
+        let info = store::describe();
+        kprintln!(st, "{} {} {} tty0", info.company, info.name, info.version);
         kprintln!(st, "Run 'help' to get started!");
+        if gop_failed {
+            kprintln!(st, "No graphics output; using the text console");
+        }
     }
 
     {
@@ -61,53 +298,248 @@ pub fn run(st: &mut SystemTable<Boot>) -> ! {
     }
 
     let mut line = heapless::String::<256>::new();
-    const HISTORY_CAP: usize = 32;
-    let mut history: heapless::Vec<heapless::String<256>, HISTORY_CAP> = heapless::Vec::new();
     let mut hist_nav: Option<usize> = None;
-    let cwd = "~";
 
     struct CommandEntry {
         name: &'static str,
         help: &'static str,
-        run: fn(st: &mut SystemTable<Boot>, args: &str),
+        run: fn(
+            st: &mut SystemTable<Boot>,
+            sink: &mut Sink,
+            shell: &mut Shell,
+            args: &str,
+        ) -> ProgramResult,
     }
 
-    fn cmd_clear(st: &mut SystemTable<Boot>, _args: &str) {
-        let _ = st.stdout().clear();
+    fn cmd_help(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        outln!(st, sink, "Commands:");
+        for c in COMMANDS {
+            outln!(st, sink, "  {:<12} {}", c.name, c.help);
+        }
+        ProgramResult::Ok
     }
 
-    fn cmd_programs(st: &mut SystemTable<Boot>, _args: &str) {
-        kprintln!(st, "Programs: {}", list_programs());
+    fn cmd_clear(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let _ = console::stdout(st).clear();
+        ProgramResult::Ok
     }
 
-    fn cmd_run(st: &mut SystemTable<Boot>, args: &str) {
-        let name = args.trim();
+    fn cmd_programs(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        outln!(st, sink, "Programs: {}", list_programs());
+        ProgramResult::Ok
+    }
+
+    fn cmd_run(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let (name, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
         if name.is_empty() {
-            kprintln!(st, "Usage: run <name>");
-            return;
+            kprintln!(st, "Usage: run <name> [args]");
+            return ProgramResult::error("usage");
         }
         if let Some(p) = find_program(name) {
             kprintln!(st, "Launching '{}'...", p.name);
-            (p.run)(st);
-            kprintln!(st, "Program '{}' exited.", p.name);
+            let result = (p.run)(st, sink, &shell.cwd, rest.trim());
+            match &result {
+                ProgramResult::Ok => kprintln!(st, "Program '{}' exited (ok).", p.name),
+                ProgramResult::Exit => kprintln!(st, "Program '{}' exited (quit).", p.name),
+                ProgramResult::Error(msg) => {
+                    kprintln!(st, "Program '{}' exited (error: {}).", p.name, msg)
+                }
+            }
+            result
         } else {
             kprintln!(st, "No such program: {}", name);
+            ProgramResult::error("no such program")
         }
     }
 
-    fn cmd_ls(st: &mut SystemTable<Boot>, _args: &str) {
-        let mut entries: Vec<heapless::String<64>, 128> = Vec::new();
-        nori::list_root(st, |name| {
-            let mut s = heapless::String::<64>::new();
-            let _ = core::fmt::Write::write_fmt(&mut s, format_args!("{}", name));
-            let _ = entries.push(s);
+    /// Lists `cwd`, a directory relative to it, or the entries matching a pattern.
+    /// `-l` adds a type column and sizes.
+    fn cmd_ls(st: &mut SystemTable<Boot>, sink: &mut Sink, cwd: &str, args: &str) -> ProgramResult {
+        let mut long = false;
+        let mut recursive = false;
+        let mut target = None;
+        for arg in args.split_whitespace() {
+            match arg {
+                "-R" => recursive = true,
+                "-l" => long = true,
+                _ if arg.starts_with('-') || target.is_some() => {
+                    kprintln!(st, "Usage: ls [-R <dir> | [-l] <dir> | <pattern>]");
+                    return ProgramResult::error("usage");
+                }
+                _ => target = Some(arg),
+            }
+        }
+        let dir = match target {
+            Some(arg) if arg.contains(['*', '?']) => {
+                if recursive {
+                    kprintln!(st, "ls: -R takes a directory, not a pattern");
+                    return ProgramResult::error("usage");
+                }
+                return ls_glob(st, sink, cwd, arg);
+            }
+            Some(arg) => join_path(cwd, arg),
+            None => join_path(cwd, "."),
+        };
+        let Some(dir) = dir else {
+            kprintln!(st, "ls: path too long");
+            return ProgramResult::error("path too long");
+        };
+        if recursive {
+            return ls_recursive(st, sink, &dir);
+        }
+        let now = st
+            .runtime_services()
+            .get_time()
+            .ok()
+            .map(|t| epoch_secs(&t));
+        let mut entries: Vec<LsEntry, 128> = Vec::new();
+        let mut truncated = false;
+        let listed = nori::list_dir_info(st, &dir, |info| {
+            let mut name = heapless::String::<64>::new();
+            let _ = core::fmt::Write::write_fmt(&mut name, format_args!("{}", info.file_name()));
+            let recent = match now {
+                Some(now) => {
+                    let age = now - epoch_secs(info.modification_time());
+                    (0..=RECENT_SECS).contains(&age)
+                }
+                None => false,
+            };
+            truncated |= entries
+                .push(LsEntry {
+                    name,
+                    is_dir: info.is_directory(),
+                    size: info.file_size(),
+                    recent,
+                })
+                .is_err();
+        });
+        if let Err(e) = listed {
+            report_read_error(st, &dir, e);
+            return ProgramResult::error("ls failed");
+        }
+        entries.sort_unstable_by(|a, b| {
+            let a_name = a.name.bytes().map(|c| c.to_ascii_lowercase());
+            let b_name = b.name.bytes().map(|c| c.to_ascii_lowercase());
+            b.is_dir.cmp(&a.is_dir).then_with(|| a_name.cmp(b_name))
         });
-        for s in entries.iter() {
-            kprintln!(st, "{}", s);
+        let width = entries
+            .iter()
+            .map(|e| decimal_width(e.size))
+            .max()
+            .unwrap_or(1);
+        for e in entries.iter() {
+            if long {
+                let kind = if e.is_dir { 'd' } else { '-' };
+                let _ = write!(console::out(st, sink), "{} {:>width$} ", kind, e.size);
+            }
+            let fg = match (e.recent, e.is_dir && long) {
+                (true, _) => Some(RECENT_FG),
+                (false, true) => Some(DIR_FG),
+                _ => None,
+            };
+            match fg {
+                Some(fg) if sink.is_console() => {
+                    set_text_color(st, fg, console::theme().1);
+                    outln!(st, sink, "{}", e.name);
+                    reset_text_color(st);
+                }
+                _ => outln!(st, sink, "{}", e.name),
+            }
+        }
+        if truncated {
+            outln!(st, sink, "... (listing truncated)");
         }
+        ProgramResult::Ok
     }
 
-    fn cmd_fs_handles(st: &mut SystemTable<Boot>, _args: &str) {
+    /// Lists the entries matching a `*`/`?` pattern such as `docs/*.txt`,
+    /// relative to `cwd` unless it starts with `/`.
+    fn ls_glob(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        cwd: &str,
+        pattern: &str,
+    ) -> ProgramResult {
+        let (dir, pattern) = match pattern.rsplit_once(['/', '\\']) {
+            Some(("", pattern)) => (join_path(cwd, "/"), pattern),
+            Some((dir, pattern)) => (join_path(cwd, dir), pattern),
+            None => (join_path(cwd, "."), pattern),
+        };
+        let Some(dir) = dir else {
+            kprintln!(st, "ls: path too long");
+            return ProgramResult::error("path too long");
+        };
+        let dir = dir.as_str();
+        let mut names: Vec<heapless::String<64>, 128> = Vec::new();
+        let globbed = nori::with_boot_fs(st.boot_services(), |fs| {
+            nori::glob(fs, dir, pattern, |entry| {
+                let mut s = heapless::String::<64>::new();
+                let _ = core::fmt::Write::write_fmt(&mut s, format_args!("{}", entry.name));
+                let _ = names.push(s);
+            })
+        });
+        if let Err(e) = globbed {
+            report_read_error(st, dir, e);
+            return ProgramResult::error("ls failed");
+        }
+        for name in names.iter() {
+            outln!(st, sink, "{}", name);
+        }
+        ProgramResult::Ok
+    }
+
+    /// Lists everything below `dir` as an indented tree.
+    fn ls_recursive(st: &mut SystemTable<Boot>, sink: &mut Sink, dir: &str) -> ProgramResult {
+        let mut entries: Vec<(heapless::String<64>, usize), 256> = Vec::new();
+        let mut truncated = false;
+        let walked = nori::UefiFs::new(st.boot_services()).and_then(|fs| {
+            nori::walk(&fs, dir, LS_MAX_DEPTH, |name, depth| {
+                let mut s = heapless::String::<64>::new();
+                let _ = core::fmt::Write::write_fmt(&mut s, format_args!("{}", name));
+                truncated |= entries.push((s, depth)).is_err();
+            })
+        });
+        for (name, depth) in entries.iter() {
+            outln!(st, sink, "{:indent$}{}", "", name, indent = depth * 2);
+        }
+        if truncated {
+            outln!(st, sink, "... (listing truncated)");
+        }
+        match walked {
+            Ok(()) => ProgramResult::Ok,
+            Err(e) => {
+                kprintln!(st, "ls: {}: {:?}", dir, e.status());
+                ProgramResult::error("walk failed")
+            }
+        }
+    }
+
+    fn cmd_fs_handles(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
         let count = {
             let bt = st.boot_services();
             match bt.locate_handle_buffer(SearchType::ByProtocol(&SimpleFileSystem::GUID)) {
@@ -115,126 +547,707 @@ pub fn run(st: &mut SystemTable<Boot>) -> ! {
                 Err(_) => 0,
             }
         };
-        kprintln!(st, "Filesystems found: {}", count);
+        outln!(st, sink, "Filesystems found: {}", count);
+        if count == 0 {
+            return ProgramResult::error("no filesystem");
+        }
+        ProgramResult::Ok
     }
 
-    fn cmd_pwd(st: &mut SystemTable<Boot>, _args: &str) {
-        kprintln!(st, "/");
+    fn cmd_df(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        match nori::with_boot_fs(st.boot_services(), nori::volume_info) {
+            Ok(info) => {
+                const MIB: u64 = 1024 * 1024;
+                let _ = write!(
+                    console::out(st, sink),
+                    "/: {} MiB free of {} MiB",
+                    info.free_bytes / MIB,
+                    info.total_bytes / MIB
+                );
+                if info.label.is_empty() {
+                    outln!(st, sink, "");
+                } else {
+                    outln!(st, sink, " [{}]", info.label);
+                }
+                ProgramResult::Ok
+            }
+            Err(e) => {
+                report_read_error(st, "/", e);
+                ProgramResult::error("df failed")
+            }
+        }
     }
 
-    fn cmd_cat(st: &mut SystemTable<Boot>, args: &str) {
+    fn cmd_cat(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        cwd: &str,
+        args: &str,
+    ) -> ProgramResult {
         let name = args.trim();
         if name.is_empty() {
             kprintln!(st, "Usage: cat <filename>");
-            return;
+            return ProgramResult::error("usage");
         }
-
-        let mut wbuf = [0u16; 260];
-        let c16 = match uefi::CStr16::from_str_with_buf(name, &mut wbuf) {
-            Ok(s) => s,
-            Err(_) => {
-                kprintln!(st, "Invalid filename");
-                return;
-            }
-        };
-
-        enum CatOutcome {
-            Data(alloc::vec::Vec<u8>),
-            IsDir,
-            NoFs,
-            OpenVolumeErr,
-            OpenErr,
-            ReadErr,
-            StatErr,
-        }
-
-        //todo: its obvious what needs to be done
-        let outcome = {
-            match nori::get_sfs(st.boot_services()) {
-                Ok(mut sfs) => match sfs.open_volume() {
-                    Ok(mut root) => match root.open(c16, FileMode::Read, FileAttribute::empty()) {
-                        Ok(file) => match file.into_type() {
-                            Ok(FileType::Regular(mut reg)) => {
-                                let mut buf = [0u8; 1024];
-                                let mut collected: alloc::vec::Vec<u8> = alloc::vec::Vec::new();
-                                let mut err: Option<CatOutcome> = None;
-                                loop {
-                                    match reg.read(&mut buf) {
-                                        Ok(read) => {
-                                            if read == 0 {
-                                                break;
-                                            }
-                                            collected.extend_from_slice(&buf[..read]);
-                                        }
-                                        Err(_) => {
-                                            err = Some(CatOutcome::ReadErr);
-                                            break;
-                                        }
-                                    }
-                                }
-                                match err {
-                                    Some(e) => e,
-                                    None => CatOutcome::Data(collected),
-                                }
-                            }
-                            Ok(FileType::Dir(_)) => CatOutcome::IsDir,
-                            Err(_) => CatOutcome::StatErr,
-                        },
-                        Err(_) => CatOutcome::OpenErr,
-                    },
-                    Err(_) => CatOutcome::OpenVolumeErr,
-                },
-                Err(_) => CatOutcome::NoFs,
-            }
+        let Some(path) = join_path(cwd, name) else {
+            kprintln!(st, "cat: path too long");
+            return ProgramResult::error("path too long");
         };
 
-        match outcome {
-            CatOutcome::Data(bytes) => {
+        match nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, &path)) {
+            Ok(bytes) => {
                 for &b in bytes.iter() {
                     let ch = b as char;
                     match ch {
                         '\r' => {}
                         '\n' => {
-                            let _ = writeln!(st.stdout(), "");
+                            let _ = writeln!(console::out(st, sink));
                         }
                         _ if ch.is_ascii_graphic() || ch == ' ' => {
-                            let _ = write!(st.stdout(), "{}", ch);
+                            let _ = write!(console::out(st, sink), "{}", ch);
                         }
                         _ => {}
                     }
                 }
-                kprintln!(st, "");
+                outln!(st, sink, "");
+                ProgramResult::Ok
+            }
+            Err(e) => {
+                report_read_error(st, name, e);
+                ProgramResult::error("read failed")
+            }
+        }
+    }
+
+    fn cmd_cp(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let mut recursive = false;
+        let mut merge = false;
+        let mut paths: heapless::Vec<&str, 2> = heapless::Vec::new();
+        for arg in args.split_whitespace() {
+            match arg {
+                "-r" => recursive = true,
+                "-m" => merge = true,
+                "-rm" | "-mr" => {
+                    recursive = true;
+                    merge = true;
+                }
+                _ if arg.starts_with('-') => {
+                    kprintln!(st, "cp: unknown option {}", arg);
+                    return ProgramResult::error("usage");
+                }
+                _ => {
+                    if paths.push(arg).is_err() {
+                        kprintln!(st, "cp: too many arguments");
+                        return ProgramResult::error("usage");
+                    }
+                }
+            }
+        }
+        let [src, dst] = paths[..] else {
+            kprintln!(st, "Usage: cp [-r [-m]] <src> <dst>");
+            return ProgramResult::error("usage");
+        };
+        let (Some(from), Some(to)) = (join_path(&shell.cwd, src), join_path(&shell.cwd, dst))
+        else {
+            kprintln!(st, "cp: path too long");
+            return ProgramResult::error("path too long");
+        };
+
+        if !recursive && from.eq_ignore_ascii_case(&to) {
+            kprintln!(st, "cp: {} and {} are the same file", src, dst);
+            return ProgramResult::error("same file");
+        }
+        if recursive && nori::is_within(&from, &to) {
+            kprintln!(st, "cp: cannot copy {} into itself", src);
+            return ProgramResult::error("copy into itself");
+        }
+
+        enum Failure {
+            NoFs,
+            Exists,
+            Fs(uefi::Error),
+        }
+        let result = match nori::UefiFs::new(st.boot_services()) {
+            Err(_) => Err(Failure::NoFs),
+            Ok(fs) if recursive && !merge && nori::exists(&fs, &to) => Err(Failure::Exists),
+            Ok(fs) if recursive => nori::copy_dir(&fs, &from, &to).map_err(Failure::Fs),
+            Ok(fs) => nori::copy_file(&fs, &from, &to).map_err(Failure::Fs),
+        };
+        match result {
+            Ok(()) => return ProgramResult::Ok,
+            Err(Failure::NoFs) => kprintln!(st, "cp: no filesystem"),
+            Err(Failure::Exists) => {
+                kprintln!(st, "cp: {} already exists (use -m to merge)", dst)
+            }
+            Err(Failure::Fs(e)) => kprintln!(st, "cp: {} -> {}: {:?}", src, dst, e.status()),
+        }
+        ProgramResult::error("copy failed")
+    }
+
+    fn cmd_rm(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let name = args.trim();
+        if name.is_empty() {
+            kprintln!(st, "Usage: rm <path>");
+            return ProgramResult::error("usage");
+        }
+        let Some(path) = join_path(&shell.cwd, name) else {
+            kprintln!(st, "rm: path too long");
+            return ProgramResult::error("path too long");
+        };
+        let mut prompt = heapless::String::<280>::new();
+        let _ = write!(prompt, "Delete {}?", path);
+        if !confirm(st, &prompt, false) {
+            return ProgramResult::Ok;
+        }
+        match nori::with_boot_fs(st.boot_services(), |fs| nori::delete(fs, &path)) {
+            Ok(()) => ProgramResult::Ok,
+            Err(e) => {
+                report_read_error(st, name, e);
+                ProgramResult::error("rm failed")
+            }
+        }
+    }
+
+    fn cmd_mv(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let mut paths = args.split_whitespace();
+        let (Some(src), Some(dst), None) = (paths.next(), paths.next(), paths.next()) else {
+            kprintln!(st, "Usage: mv <src> <dst>");
+            return ProgramResult::error("usage");
+        };
+        let (Some(from), Some(to)) = (join_path(&shell.cwd, src), join_path(&shell.cwd, dst))
+        else {
+            kprintln!(st, "mv: path too long");
+            return ProgramResult::error("path too long");
+        };
+        match nori::with_boot_fs(st.boot_services(), |fs| nori::rename(fs, &from, &to)) {
+            Ok(()) => ProgramResult::Ok,
+            Err(NoriError::AlreadyExists) => {
+                report_read_error(st, dst, NoriError::AlreadyExists);
+                ProgramResult::error("mv failed")
+            }
+            Err(e) => {
+                report_read_error(st, src, e);
+                ProgramResult::error("mv failed")
+            }
+        }
+    }
+
+    fn cmd_exists(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let name = args.trim();
+        if name.is_empty() {
+            kprintln!(st, "Usage: exists <path>");
+            return ProgramResult::error("usage");
+        }
+        let Some(path) = join_path(&shell.cwd, name) else {
+            return ProgramResult::error("path too long");
+        };
+        match nori::UefiFs::new(st.boot_services()) {
+            Ok(fs) if nori::exists(&fs, &path) => ProgramResult::Ok,
+            _ => ProgramResult::error("not found"),
+        }
+    }
+
+    fn cmd_od(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let mut addr_radix = 'x';
+        let mut byte_fmt = 'o';
+        let mut file = None;
+        let mut tokens = args.split_whitespace();
+        while let Some(tok) = tokens.next() {
+            let (flag, inline) = match tok {
+                t if t.starts_with("-A") || t.starts_with("-t") => t.split_at(2),
+                t => {
+                    file = Some(t);
+                    continue;
+                }
+            };
+            let value = if inline.is_empty() {
+                tokens.next().unwrap_or("")
+            } else {
+                inline
+            };
+            match (flag, value) {
+                ("-A", "x" | "d" | "o") => addr_radix = value.as_bytes()[0] as char,
+                ("-t", "x1" | "d1" | "o1") => byte_fmt = value.as_bytes()[0] as char,
+                _ => {
+                    kprintln!(st, "Usage: od [-A x|d|o] [-t x1|d1|o1] <file>");
+                    return ProgramResult::error("usage");
+                }
+            }
+        }
+        let Some(name) = file else {
+            kprintln!(st, "Usage: od [-A x|d|o] [-t x1|d1|o1] <file>");
+            return ProgramResult::error("usage");
+        };
+
+        let Some(bytes) = read_relative(st, "od", &shell.cwd, name) else {
+            return ProgramResult::error("read failed");
+        };
+        let write_addr = |st: &mut SystemTable<Boot>, sink: &mut Sink, addr: usize| {
+            let _ = match addr_radix {
+                'd' => write!(console::out(st, sink), "{:07}", addr),
+                'o' => write!(console::out(st, sink), "{:07o}", addr),
+                _ => write!(console::out(st, sink), "{:07x}", addr),
+            };
+        };
+        for (row, chunk) in bytes.chunks(16).enumerate() {
+            write_addr(st, sink, row * 16);
+            for &b in chunk {
+                let _ = match byte_fmt {
+                    'x' => write!(console::out(st, sink), " {:02x}", b),
+                    'd' => write!(console::out(st, sink), " {:4}", b as i8),
+                    _ => write!(console::out(st, sink), " {:03o}", b),
+                };
+            }
+            outln!(st, sink, "");
+        }
+        write_addr(st, sink, bytes.len());
+        outln!(st, sink, "");
+        ProgramResult::Ok
+    }
+
+    fn cmd_seq(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let mut nums = args.split_whitespace().map(|a| a.parse::<i64>());
+        let range = match (nums.next(), nums.next(), nums.next()) {
+            (Some(Ok(end)), None, None) => Some((1, end)),
+            (Some(Ok(start)), Some(Ok(end)), None) => Some((start, end)),
+            _ => None,
+        };
+        let Some((start, end)) = range.filter(|&(start, end)| {
+            end.checked_sub(start)
+                .is_some_and(|span| span < SEQ_MAX_COUNT)
+        }) else {
+            kprintln!(
+                st,
+                "Usage: seq <n> | seq <start> <end>  (at most {} numbers)",
+                SEQ_MAX_COUNT
+            );
+            return ProgramResult::error("usage");
+        };
+        let mut keys = KeyReader::new();
+        for i in start..=end {
+            if sink.is_console() && keys.ctrl_c(st) {
+                kprintln!(st, "^C");
+                return ProgramResult::error("interrupted");
+            }
+            outln!(st, sink, "{}", i);
+        }
+        ProgramResult::Ok
+    }
+
+    fn cmd_printf(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let (format, rest) = split_first_arg(args.trim_start());
+        if format.is_empty() {
+            kprintln!(st, "Usage: printf <format> [args...]");
+            return ProgramResult::error("usage");
+        }
+        let mut values = rest.split_whitespace();
+        let mut bad: Option<char> = None;
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => outln!(st, sink, ""),
+                    Some('t') => {
+                        let _ = write!(console::out(st, sink), "\t");
+                    }
+                    Some('\\') => {
+                        let _ = write!(console::out(st, sink), "\\");
+                    }
+                    Some(other) => {
+                        let _ = write!(console::out(st, sink), "\\{}", other);
+                    }
+                    None => {
+                        let _ = write!(console::out(st, sink), "\\");
+                    }
+                },
+                '%' => match chars.next() {
+                    Some('%') => {
+                        let _ = write!(console::out(st, sink), "%");
+                    }
+                    Some('s') => {
+                        let _ = write!(console::out(st, sink), "{}", values.next().unwrap_or(""));
+                    }
+                    Some('d') => {
+                        let n = values.next().and_then(|v| v.parse::<i64>().ok());
+                        let _ = write!(console::out(st, sink), "{}", n.unwrap_or(0));
+                    }
+                    Some('x') => {
+                        let n = values.next().and_then(|v| v.parse::<i64>().ok());
+                        let _ = write!(console::out(st, sink), "{:x}", n.unwrap_or(0));
+                    }
+                    Some(other) => {
+                        bad.get_or_insert(other);
+                        let _ = write!(console::out(st, sink), "%{}", other);
+                    }
+                    None => {
+                        bad.get_or_insert('%');
+                        let _ = write!(console::out(st, sink), "%");
+                    }
+                },
+                _ => {
+                    let _ = write!(console::out(st, sink), "{}", c);
+                }
             }
-            CatOutcome::IsDir => {
-                kprintln!(st, "{}: is a directory", name);
+        }
+        if let Some(c) = bad {
+            outln!(st, sink, "");
+            kprintln!(st, "printf: invalid directive '%{}' printed literally", c);
+            return ProgramResult::error("invalid directive");
+        }
+        ProgramResult::Ok
+    }
+
+    fn cmd_mem(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let Some(heap) = shared::heap::stats() else {
+            kprintln!(st, "mem: heap not initialized");
+            return ProgramResult::error("no heap");
+        };
+        outln!(
+            st,
+            sink,
+            "Heap: {} KiB used, {} KiB free, {} KiB total",
+            heap.used / 1024,
+            heap.free / 1024,
+            heap.total() / 1024
+        );
+        ProgramResult::Ok
+    }
+
+    fn cmd_memmap(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let summary = match args.trim() {
+            "" => false,
+            "-s" => true,
+            _ => {
+                kprintln!(st, "Usage: memmap [-s]");
+                return ProgramResult::error("usage");
             }
-            CatOutcome::NoFs => {
-                kprintln!(st, "No filesystem available");
+        };
+        // The map can grow between asking for its size and reading it (the
+        // buffer allocation itself may split a region), so leave slack and retry.
+        let mut slack = 8;
+        let mut buf: alloc::vec::Vec<u64>;
+        let map = loop {
+            let size = st.boot_services().memory_map_size();
+            buf = alloc::vec![0; (size.map_size + slack * size.entry_size).div_ceil(8)];
+            // SAFETY: the bytes of a `Vec<u64>` are initialized, and the
+            // u64 alignment suits the descriptors written into them.
+            let bytes = unsafe {
+                core::slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), buf.len() * 8)
+            };
+            match st.boot_services().memory_map(bytes) {
+                Ok(map) => break map,
+                Err(e) if e.status() == Status::BUFFER_TOO_SMALL && slack < 64 => slack *= 2,
+                Err(e) => {
+                    kprintln!(st, "memmap: {:?}", e.status());
+                    return ProgramResult::error("memory_map failed");
+                }
             }
-            CatOutcome::OpenVolumeErr => {
-                kprintln!(st, "Failed to open root volume");
+        };
+
+        let mut totals: Vec<(MemoryType, u64), 32> = Vec::new();
+        if !summary {
+            outln!(
+                st,
+                sink,
+                "{:<22} {:>16} {:>8} {:>18}",
+                "Type",
+                "Start",
+                "Pages",
+                "Attributes"
+            );
+        }
+        for desc in map.entries() {
+            if !summary {
+                outln!(
+                    st,
+                    sink,
+                    "{:<22} {:>#16x} {:>8} {:>#18x}",
+                    alloc::format!("{:?}", desc.ty),
+                    desc.phys_start,
+                    desc.page_count,
+                    desc.att.bits()
+                );
             }
-            CatOutcome::OpenErr => {
-                kprintln!(st, "Cannot open: {}", name);
+            match totals.iter_mut().find(|(ty, _)| *ty == desc.ty) {
+                Some((_, pages)) => *pages += desc.page_count,
+                None => {
+                    let _ = totals.push((desc.ty, desc.page_count));
+                }
+            }
+        }
+        outln!(st, sink, "Totals:");
+        for (ty, pages) in totals.iter() {
+            outln!(
+                st,
+                sink,
+                "  {:<22} {:>8} pages {:>8} KiB",
+                alloc::format!("{:?}", ty),
+                pages,
+                pages * 4
+            );
+        }
+        ProgramResult::Ok
+    }
+
+    fn cmd_lspci(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let mut found: alloc::vec::Vec<pci::Function> = alloc::vec::Vec::new();
+        pci::scan(|f| found.push(*f));
+        for f in found.iter() {
+            outln!(
+                st,
+                sink,
+                "{:02x}:{:02x}.{} {:04x}:{:04x} {:02x}{:02x}",
+                f.bus,
+                f.device,
+                f.function,
+                f.vendor_id,
+                f.device_id,
+                f.class,
+                f.subclass
+            );
+        }
+        if found.is_empty() {
+            outln!(st, sink, "lspci: no PCI devices found");
+        }
+        ProgramResult::Ok
+    }
+
+    fn cmd_uptime(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let now = st.runtime_services().get_time().ok();
+        let (Some(boot), Some(now)) = (store::boot_time(), now) else {
+            kprintln!(st, "uptime: no clock available");
+            return ProgramResult::error("no clock");
+        };
+        let secs = (epoch_secs(&now) as u64).saturating_sub(boot);
+        outln!(
+            st,
+            sink,
+            "up {:02}:{:02}:{:02}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+        ProgramResult::Ok
+    }
+
+    fn cmd_sleep(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        _shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let secs = match args.trim().parse::<u64>() {
+            Ok(n) if n <= SLEEP_MAX_SECS => n,
+            _ => {
+                kprintln!(st, "Usage: sleep <seconds>  (at most {})", SLEEP_MAX_SECS);
+                return ProgramResult::error("usage");
             }
-            CatOutcome::ReadErr => {
-                kprintln!(st, "Read error");
+        };
+        if KeyReader::new().sleep(st, secs * 1000) {
+            ProgramResult::Ok
+        } else {
+            kprintln!(st, "^C");
+            ProgramResult::error("interrupted")
+        }
+    }
+
+    fn cmd_date(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let now = match st.runtime_services().get_time() {
+            Ok(now) => now,
+            Err(e) if e.status() == Status::UNSUPPORTED => {
+                kprintln!(st, "date: the firmware has no real-time clock");
+                return ProgramResult::error("unsupported");
             }
-            CatOutcome::StatErr => {
-                kprintln!(st, "Failed to stat file");
+            Err(e) => {
+                kprintln!(st, "date: {:?}", e.status());
+                return ProgramResult::error("get_time failed");
             }
+        };
+        let mut line = heapless::String::<64>::new();
+        let _ = write!(
+            line,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            now.year(),
+            now.month(),
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second()
+        );
+        // The firmware's offset from UTC in minutes, when it knows one.
+        if let Some(tz) = now.time_zone() {
+            let sign = if tz < 0 { '-' } else { '+' };
+            let tz = tz.unsigned_abs();
+            let _ = write!(line, " {}{:02}:{:02}", sign, tz / 60, tz % 60);
+        }
+        if now.daylight().contains(Daylight::IN_DAYLIGHT) {
+            let _ = line.push_str(" DST");
+        }
+        outln!(st, sink, "{}", line);
+        ProgramResult::Ok
+    }
+
+    fn cmd_version(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let info = store::describe();
+        outln!(st, sink, "{} {} {}", info.company, info.name, info.version);
+        outln!(st, sink, "Firmware: {}", info.firmware);
+        outln!(st, sink, "Programs: {}", list_programs());
+        ProgramResult::Ok
+    }
+
+    fn cmd_reboot(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        if confirm(st, "Reboot now?", false) {
+            st.runtime_services()
+                .reset(ResetType::COLD, Status::SUCCESS, None);
+        }
+        ProgramResult::Ok
+    }
+
+    fn cmd_shutdown(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        if confirm(st, "Power off now?", false) {
+            st.runtime_services()
+                .reset(ResetType::SHUTDOWN, Status::SUCCESS, None);
+        }
+        ProgramResult::Ok
+    }
+
+    fn cmd_pwd(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        outln!(st, sink, "{}", shell.cwd);
+        ProgramResult::Ok
+    }
+
+    fn cmd_unalias(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        let name = args.trim();
+        let before = shell.aliases.len();
+        shell.aliases.retain(|(n, _)| n != name);
+        if shell.aliases.len() == before {
+            kprintln!(st, "unalias: {}: not found", name);
+            ProgramResult::error("not found")
+        } else {
+            ProgramResult::Ok
+        }
+    }
+
+    fn cmd_history(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        let first = shell.history_count + 1 - shell.history.len();
+        for (i, entry) in shell.history.iter().enumerate() {
+            outln!(st, sink, "{:5}  {}", first + i, entry);
         }
+        ProgramResult::Ok
     }
 
-    fn x_debug_panic(_st: &mut SystemTable<Boot>, _args: &str) {
-        panic!("Test panic");
+    fn x_debug_panic(
+        st: &mut SystemTable<Boot>,
+        _sink: &mut Sink,
+        _shell: &mut Shell,
+        _args: &str,
+    ) -> ProgramResult {
+        if confirm(st, "Trigger a kernel panic?", false) {
+            panic!("Test panic");
+        }
+        ProgramResult::Ok
     }
 
     static COMMANDS: &[CommandEntry] = &[
         CommandEntry {
             name: "help",
             help: "Show this help",
-            run: |_st, _args| {},
+            run: cmd_help,
         },
         CommandEntry {
             name: "clear",
@@ -248,28 +1261,178 @@ pub fn run(st: &mut SystemTable<Boot>) -> ! {
         },
         CommandEntry {
             name: "run",
-            help: "Run a program: run <name>",
+            help: "Run a program: run <name> [args]",
             run: cmd_run,
         },
         CommandEntry {
             name: "ls",
-            help: "List root directory",
-            run: cmd_ls,
+            help: "List a directory (default: current); -l adds sizes, -R lists the whole tree, ls <pattern> filters",
+            run: |st, sink, shell, args| cmd_ls(st, sink, &shell.cwd, args),
         },
         CommandEntry {
             name: "pwd",
             help: "Print current directory",
             run: cmd_pwd,
         },
+        CommandEntry {
+            name: "cd",
+            help: "Change directory: cd <path> | cd -",
+            run: |st, sink, shell, args| change_dir(st, sink, &mut shell.cwd, &mut shell.oldpwd, args),
+        },
         CommandEntry {
             name: "fs-handles",
             help: "Count available filesystems",
             run: cmd_fs_handles,
         },
+        CommandEntry {
+            name: "df",
+            help: "Show free space on the volume",
+            run: cmd_df,
+        },
         CommandEntry {
             name: "cat",
             help: "Show file contents: cat <name>",
-            run: cmd_cat,
+            run: |st, sink, shell, args| cmd_cat(st, sink, &shell.cwd, args),
+        },
+        CommandEntry {
+            name: "grep",
+            help: "Print matching lines: grep [-i] <pattern> <name>",
+            run: |st, sink, shell, args| cmd_grep(st, sink, &shell.cwd, args),
+        },
+        CommandEntry {
+            name: "wc",
+            help: "Count lines, words and bytes: wc <name>",
+            run: |st, sink, shell, args| cmd_wc(st, sink, &shell.cwd, args),
+        },
+        CommandEntry {
+            name: "head",
+            help: "Show the first lines of a file: head [-n N] <name>",
+            run: |st, sink, shell, args| cmd_head(st, sink, &shell.cwd, args),
+        },
+        CommandEntry {
+            name: "tail",
+            help: "Show the last lines of a file: tail [-n N] <name>",
+            run: |st, sink, shell, args| cmd_tail(st, sink, &shell.cwd, args),
+        },
+        CommandEntry {
+            name: "hexdump",
+            help: "Show file bytes in hex: hexdump <name> [count]",
+            run: |st, sink, shell, args| cmd_hexdump(st, sink, &shell.cwd, args),
+        },
+        CommandEntry {
+            name: "cp",
+            help: "Copy files: cp <src> <dst> | cp -r [-m] <dir> <dst>",
+            run: cmd_cp,
+        },
+        CommandEntry {
+            name: "mv",
+            help: "Move a file: mv <src> <dst>",
+            run: cmd_mv,
+        },
+        CommandEntry {
+            name: "rm",
+            help: "Delete a file or empty directory: rm <path>",
+            run: cmd_rm,
+        },
+        CommandEntry {
+            name: "exists",
+            help: "Succeed if a path exists (for if): exists <path>",
+            run: cmd_exists,
+        },
+        CommandEntry {
+            name: "alias",
+            help: "Command shortcuts: alias | alias <name>='<cmd>'",
+            run: |st, sink, shell, args| alias_command(st, sink, &mut shell.aliases, args),
+        },
+        CommandEntry {
+            name: "unalias",
+            help: "Remove an alias: unalias <name>",
+            run: cmd_unalias,
+        },
+        CommandEntry {
+            name: "history",
+            help: "List history; rerun with !! or !<n>",
+            run: cmd_history,
+        },
+        CommandEntry {
+            name: "theme",
+            help: "Set text colors: theme <fg> <bg>; no args lists colors",
+            run: |st, sink, shell, args| theme_command(st, sink, &mut shell.config, args),
+        },
+        CommandEntry {
+            name: "config",
+            help: "Boot settings: config | config get <key> | config set <key> <value>",
+            run: |st, sink, shell, args| config_command(st, sink, &mut shell.config, args),
+        },
+        CommandEntry {
+            name: "watch",
+            help: "Re-run a command until a key is pressed: watch <secs> <cmd>",
+            run: watch,
+        },
+        CommandEntry {
+            name: "seq",
+            help: "Print a sequence: seq <n> | seq <start> <end>",
+            run: cmd_seq,
+        },
+        CommandEntry {
+            name: "printf",
+            help: "Formatted output: printf <format> [args...]",
+            run: cmd_printf,
+        },
+        CommandEntry {
+            name: "od",
+            help: "Dump a file: od [-A x|d|o] [-t x1|d1|o1] <name>",
+            run: cmd_od,
+        },
+        CommandEntry {
+            name: "mem",
+            help: "Show kernel heap usage",
+            run: cmd_mem,
+        },
+        CommandEntry {
+            name: "sleep",
+            help: "Wait a number of seconds (Ctrl+C stops)",
+            run: cmd_sleep,
+        },
+        CommandEntry {
+            name: "date",
+            help: "Show the date and time from the firmware clock",
+            run: cmd_date,
+        },
+        CommandEntry {
+            name: "memmap",
+            help: "Show the firmware memory map; -s prints only totals",
+            run: cmd_memmap,
+        },
+        CommandEntry {
+            name: "lspci",
+            help: "List PCI devices as bus:dev.fn vendor:device class",
+            run: cmd_lspci,
+        },
+        CommandEntry {
+            name: "uptime",
+            help: "Show time since boot",
+            run: cmd_uptime,
+        },
+        CommandEntry {
+            name: "version",
+            help: "Show version information",
+            run: cmd_version,
+        },
+        CommandEntry {
+            name: "reboot",
+            help: "Restart the machine",
+            run: cmd_reboot,
+        },
+        CommandEntry {
+            name: "shutdown",
+            help: "Power off the machine",
+            run: cmd_shutdown,
+        },
+        CommandEntry {
+            name: "exit",
+            help: "Leave the shell and power off (also Ctrl+D)",
+            run: |st, _sink, _shell, _args| exit_shell(st),
         },
         CommandEntry {
             name: "x:debug-panic",
@@ -277,64 +1440,932 @@ pub fn run(st: &mut SystemTable<Boot>) -> ! {
             run: x_debug_panic,
         },
     ];
+    /// Runs one shell line: `if`, `name=value`, `$var` expansion, then dispatch.
+    fn execute(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        line: &str,
+    ) -> ProgramResult {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return ProgramResult::Ok;
+        }
+        let Some(commands) = split_commands(line) else {
+            kprintln!(
+                st,
+                "Too many commands on one line (at most {})",
+                MAX_LINE_COMMANDS
+            );
+            shell.last_ok = false;
+            return ProgramResult::error("too many commands");
+        };
+        if commands.len() > 1 {
+            let mut result = ProgramResult::Ok;
+            for command in commands {
+                result = execute(st, sink, shell, command);
+            }
+            return result;
+        }
+        if let Some(rest) = line.strip_prefix("if ") {
+            let Some((cond, body)) = parse_if(rest) else {
+                kprintln!(st, "Usage: if <cmd>; then <cmd>; fi");
+                shell.last_ok = false;
+                return ProgramResult::error("usage");
+            };
+            if execute(st, sink, shell, cond).is_success() {
+                return execute(st, sink, shell, body);
+            }
+            shell.last_ok = true;
+            return ProgramResult::Ok;
+        }
+
+        let Some(expanded) = expand_vars(line, shell) else {
+            kprintln!(st, "Line too long after expansion");
+            shell.last_ok = false;
+            return ProgramResult::error("line too long");
+        };
+        let s = expanded.as_str();
+        if let Some((cmd, target, append)) = parse_redirect(s) {
+            return redirect(st, shell, cmd, target, append);
+        }
+        let aliased = expand_alias(s, &shell.aliases);
+        let s = aliased.as_ref().map_or(s, |a| a.as_str());
+        let (cmd_name, args) = match s.split_once(' ') {
+            Some((c, rest)) => (c, rest),
+            None => (s, ""),
+        };
+
+        let result = if let Some((name, value)) = parse_assignment(s) {
+            if set_var(&mut shell.vars, name, value) {
+                ProgramResult::Ok
+            } else {
+                kprintln!(st, "{}: too many variables or value too long", name);
+                ProgramResult::error("set failed")
+            }
+        } else {
+            match COMMANDS.iter().find(|c| c.name == cmd_name) {
+                Some(c) => (c.run)(st, sink, shell, args),
+                None => run_script(st, sink, shell, cmd_name),
+            }
+        };
+        shell.last_ok = result.is_success();
+        result
+    }
+
+    /// Runs `cmd` with its output captured, then writes it to `target`
+    /// (relative to the current directory), appending for `>>`.
+    fn redirect(
+        st: &mut SystemTable<Boot>,
+        shell: &mut Shell,
+        cmd: &str,
+        target: &str,
+        append: bool,
+    ) -> ProgramResult {
+        if cmd.is_empty() || target.is_empty() {
+            kprintln!(st, "Usage: <cmd> > <file> | <cmd> >> <file>");
+            shell.last_ok = false;
+            return ProgramResult::error("usage");
+        }
+        let Some(path) = join_path(&shell.cwd, target) else {
+            kprintln!(st, "{}: path too long", target);
+            shell.last_ok = false;
+            return ProgramResult::error("path too long");
+        };
+
+        let mut capture = Sink::Capture(alloc::string::String::new());
+        let result = execute(st, &mut capture, shell, cmd);
+        let Sink::Capture(text) = capture else {
+            unreachable!()
+        };
+        if let Err(e) = nori::with_boot_fs(st.boot_services(), |fs| {
+            nori::save_file(fs, &path, text.as_bytes(), append)
+        }) {
+            report_read_error(st, target, e);
+            shell.last_ok = false;
+            return ProgramResult::error("redirect failed");
+        }
+        result
+    }
+
+    /// Clears the screen and runs `cmd` every `secs` seconds until a key is pressed.
+    /// Refused under `>`/`>>`, whose capture would only be written out at the end.
+    fn watch(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        args: &str,
+    ) -> ProgramResult {
+        if !sink.is_console() {
+            kprintln!(st, "watch: cannot redirect watch");
+            return ProgramResult::error("watch redirected");
+        }
+        let (secs, cmd) = split_first_arg(args.trim_start());
+        let cmd = cmd.trim();
+        let secs = match secs.parse::<u64>() {
+            Ok(n) if n > 0 && !cmd.is_empty() => n,
+            _ => {
+                kprintln!(st, "Usage: watch <secs> <cmd>");
+                return ProgramResult::error("usage");
+            }
+        };
+        let mut keys = KeyReader::new();
+        loop {
+            let _ = console::stdout(st).clear();
+            kprintln!(st, "Every {}s: {}    (press any key to stop)", secs, cmd);
+            kprintln!(st, "");
+            execute(st, sink, shell, cmd);
+            if keys.read_timeout(st, secs * 1000).is_some() {
+                return ProgramResult::Ok;
+            }
+        }
+    }
+
+    /// Runs `\bin\<name>` line by line, or reports an unknown command.
+    fn run_script(
+        st: &mut SystemTable<Boot>,
+        sink: &mut Sink,
+        shell: &mut Shell,
+        name: &str,
+    ) -> ProgramResult {
+        let mut path = heapless::String::<64>::new();
+        let bytes = match write!(path, "\\bin\\{}", name) {
+            Ok(()) => nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, &path)),
+            Err(_) => Err(NoriError::NotFound),
+        };
+        let bytes = match bytes {
+            Ok(bytes) => bytes,
+            Err(NoriError::NotFound | NoriError::IsDirectory) => {
+                match closest_name(name) {
+                    Some(near) => kprintln!(st, "Unknown: {} (did you mean '{}'?)", name, near),
+                    None => kprintln!(st, "Unknown: {} (try 'help')", name),
+                }
+                return ProgramResult::error("unknown command");
+            }
+            Err(e) => {
+                report_read_error(st, &path, e);
+                return ProgramResult::error("read failed");
+            }
+        };
+        if shell.script_depth >= MAX_SCRIPT_DEPTH {
+            kprintln!(st, "{}: scripts nested too deeply", name);
+            return ProgramResult::error("nested too deeply");
+        }
+        let Ok(text) = core::str::from_utf8(&bytes) else {
+            kprintln!(st, "{}: script is not UTF-8", name);
+            return ProgramResult::error("not UTF-8");
+        };
+        let mut result = ProgramResult::Ok;
+        shell.script_depth += 1;
+        for line in text.lines() {
+            result = execute(st, sink, shell, line);
+        }
+        shell.script_depth -= 1;
+        result
+    }
+
     loop {
+        let prompt = shell.prompt();
         {
-            let _ = write!(st.stdout(), "root@mochi:{}{}", cwd, PREFIX);
+            let _ = write!(console::stdout(st), "{}", prompt);
+        }
+        line.clear();
+        if !read_line_shell(
+            st,
+            &mut line,
+            &shell.history,
+            &mut hist_nav,
+            &prompt,
+            &shell.cwd,
+        ) {
+            exit_shell(st);
+        }
+        hist_nav = None;
+
+        let s = line.trim();
+        if s.is_empty() {
+            continue;
+        }
+        let expanded = match expand_history(s, &shell) {
+            Ok(expanded) => expanded,
+            Err(event) => {
+                kprintln!(st, "{}: event not found", event);
+                continue;
+            }
+        };
+        if expanded != s {
+            kprintln!(st, "{}", expanded);
+        }
+        let remembered = shell.history_count;
+        shell.remember(&expanded);
+        if shell.history_count != remembered {
+            let mut entry = heapless::String::<257>::new();
+            let _ = entry.push_str(&expanded);
+            let _ = entry.push('\n');
+            let _ = nori::with_boot_fs(st.boot_services(), |fs| {
+                nori::save_file(fs, HISTORY_PATH, entry.as_bytes(), true)
+            });
+        }
+        execute(st, &mut Sink::Console, &mut shell, &expanded);
+    }
+}
+
+/// Fills the history from `HISTORY_PATH`, if it exists, trimming the file
+/// down to the lines that were kept.
+fn load_history(st: &mut SystemTable<Boot>, shell: &mut Shell) {
+    let Ok(bytes) = nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, HISTORY_PATH))
+    else {
+        return;
+    };
+    let text = core::str::from_utf8(&bytes).unwrap_or("");
+    let lines = || text.lines().filter(|l| !l.trim().is_empty());
+    let dropped = lines().count().saturating_sub(HISTORY_CAP);
+
+    let mut kept = alloc::string::String::new();
+    for line in lines().skip(dropped) {
+        shell.remember(line);
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    if dropped > 0 {
+        let _ = nori::with_boot_fs(st.boot_services(), |fs| {
+            nori::save_file(fs, HISTORY_PATH, kept.as_bytes(), false)
+        });
+    }
+}
+
+/// `theme` lists the color names; `theme <fg> <bg>` saves and applies a pair.
+fn theme_command(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    config: &mut Config,
+    args: &str,
+) -> ProgramResult {
+    let args = args.trim();
+    if args.is_empty() {
+        outln!(st, sink, "Current theme: {}", config.theme);
+        let (_, bg) = console::theme();
+        for (name, rgb) in color::NAMES {
+            if sink.is_console() {
+                set_text_color(st, rgb, bg);
+            }
+            let _ = write!(console::out(st, sink), "{} ", name);
+        }
+        if sink.is_console() {
+            reset_text_color(st);
+        }
+        outln!(st, sink, "");
+        return ProgramResult::Ok;
+    }
+    if config::parse_theme(args).is_none() {
+        kprintln!(st, "Usage: theme <fg> <bg>  (run 'theme' for color names)");
+        return ProgramResult::error("usage");
+    }
+    let mut set = heapless::String::<64>::new();
+    let _ = write!(set, "set theme {}", args);
+    config_command(st, sink, config, &set)
+}
+
+/// Lists, reads or updates `\qubit.conf`. `set` validates the value and
+/// writes the whole file back before changing the live settings.
+fn config_command(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    config: &mut Config,
+    args: &str,
+) -> ProgramResult {
+    let (sub, rest) = split_first_arg(args.trim_start());
+    let (key, value) = split_first_arg(rest.trim_start());
+    match sub {
+        "" => {
+            for k in config::KEYS {
+                let mut value = heapless::String::<64>::new();
+                let _ = config.write_value(k.name, &mut value);
+                let note = if k.needs_reboot { "  (on reboot)" } else { "" };
+                outln!(st, sink, "{:<10} = {}{}", k.name, value, note);
+            }
+            ProgramResult::Ok
+        }
+        "get" if !key.is_empty() => {
+            let mut value = heapless::String::<64>::new();
+            if config.write_value(key, &mut value).is_err() {
+                kprintln!(st, "config: unknown key: {}", key);
+                return ProgramResult::error("unknown key");
+            }
+            outln!(st, sink, "{}", value);
+            ProgramResult::Ok
+        }
+        "set" if !key.is_empty() => {
+            let mut next = config.clone();
+            // Quotes keep trailing spaces, which the shell otherwise trims.
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            if let Err(e) = next.set(key, value) {
+                kprintln!(st, "config: {}: {}", key, e);
+                if let Some(k) = config::find_key(key) {
+                    kprintln!(st, "  {}", k.help);
+                }
+                return ProgramResult::error("invalid setting");
+            }
+            let mut text = heapless::String::<512>::new();
+            let written = next.write_to(&mut text).is_ok()
+                && nori::with_boot_fs(st.boot_services(), |fs| {
+                    nori::save_file(fs, config::PATH, text.as_bytes(), false)
+                })
+                .is_ok();
+            if !written {
+                kprintln!(st, "config: cannot write {}", config::PATH);
+                return ProgramResult::error("write failed");
+            }
+            *config = next;
+            console::set_scrollback(config.scrollback as usize);
+            if key == "theme" {
+                apply_theme(st, config);
+            }
+            if config::find_key(key).is_some_and(|k| k.needs_reboot) {
+                kprintln!(st, "{} takes effect after a reboot", key);
+            }
+            ProgramResult::Ok
+        }
+        _ => {
+            kprintln!(
+                st,
+                "Usage: config | config get <key> | config set <key> <value>"
+            );
+            ProgramResult::error("usage")
+        }
+    }
+}
+
+/// `alias` lists every alias, `alias <name>` shows one and
+/// `alias <name>=<text>` defines or replaces one.
+fn alias_command(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    aliases: &mut Vars,
+    args: &str,
+) -> ProgramResult {
+    let args = args.trim();
+    if args.is_empty() {
+        for (name, value) in aliases.iter() {
+            outln!(st, sink, "alias {}='{}'", name, value);
+        }
+        return ProgramResult::Ok;
+    }
+    if let Some((name, value)) = parse_assignment(args) {
+        if set_var(aliases, name, value) {
+            return ProgramResult::Ok;
+        }
+        kprintln!(st, "alias: {}: too many aliases or text too long", name);
+        return ProgramResult::error("alias failed");
+    }
+    match aliases.iter().find(|(n, _)| n == args) {
+        Some((name, value)) => {
+            outln!(st, sink, "alias {}='{}'", name, value);
+            ProgramResult::Ok
+        }
+        None => {
+            kprintln!(st, "alias: {}: not found", args);
+            ProgramResult::error("not found")
+        }
+    }
+}
+
+/// Replaces the command word of `line` with its alias, if it has one. The
+/// result is not looked up again, so an alias can't recurse.
+fn expand_alias(line: &str, aliases: &Vars) -> Option<heapless::String<256>> {
+    let (cmd, rest) = match line.split_once(' ') {
+        Some((cmd, rest)) => (cmd, Some(rest)),
+        None => (line, None),
+    };
+    let (_, value) = aliases.iter().find(|(name, _)| name == cmd)?;
+    let mut out = heapless::String::new();
+    out.push_str(value).ok()?;
+    if let Some(rest) = rest {
+        out.push(' ').ok()?;
+        out.push_str(rest).ok()?;
+    }
+    Some(out)
+}
+
+/// Moves `cwd` to `args`, keeping the previous directory for `cd -`.
+fn change_dir(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    cwd: &mut heapless::String<256>,
+    oldpwd: &mut Option<heapless::String<256>>,
+    args: &str,
+) -> ProgramResult {
+    let target = args.trim();
+    let next = if target == "-" {
+        match oldpwd.clone() {
+            Some(prev) => {
+                outln!(st, sink, "{}", prev);
+                prev
+            }
+            None => {
+                kprintln!(st, "cd: OLDPWD not set");
+                return ProgramResult::error("OLDPWD not set");
+            }
+        }
+    } else {
+        match join_path(cwd, target) {
+            Some(p) => p,
+            None => {
+                kprintln!(st, "cd: path too long");
+                return ProgramResult::error("path too long");
+            }
+        }
+    };
+    enum Failure {
+        NoFs,
+        NotDir,
+        Missing,
+    }
+    let resolved = match nori::UefiFs::new(st.boot_services()) {
+        Err(_) => Err(Failure::NoFs),
+        Ok(_) if next == "/" => Ok(None),
+        Ok(fs) => match nori::open_path(&fs, &next) {
+            Ok((real, entry)) if entry.is_dir => Ok(join_path("/", &real)),
+            Ok(_) => Err(Failure::NotDir),
+            Err(_) => Err(Failure::Missing),
+        },
+    };
+    let next = match resolved {
+        Ok(real) => real.unwrap_or(next),
+        Err(Failure::NoFs) => {
+            kprintln!(st, "cd: no filesystem");
+            return ProgramResult::error("no filesystem");
+        }
+        Err(Failure::NotDir) => {
+            kprintln!(st, "cd: {}: not a directory", next);
+            return ProgramResult::error("not a directory");
+        }
+        Err(Failure::Missing) => {
+            kprintln!(st, "cd: {}: no such directory", next);
+            return ProgramResult::error("no such directory");
+        }
+    };
+    *oldpwd = Some(core::mem::replace(cwd, next));
+    ProgramResult::Ok
+}
+
+/// Replaces `!!`, `!<n>` and `!-<n>` with history entries. A reference to an
+/// entry that no longer exists fails with the offending token.
+fn expand_history<'a>(line: &'a str, shell: &Shell) -> Result<heapless::String<256>, &'a str> {
+    let mut out = heapless::String::<256>::new();
+    let mut rest = line;
+    while let Some(pos) = rest.find('!') {
+        let _ = out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (n, used) = if after.starts_with('!') {
+            (Some(shell.history_count), 1)
+        } else if let Some(back) = after.strip_prefix('-') {
+            let len = digits(back);
+            let k = back[..len].parse::<usize>().ok();
+            (
+                k.and_then(|k| (shell.history_count + 1).checked_sub(k)),
+                len + 1,
+            )
+        } else {
+            let len = digits(after);
+            (after[..len].parse::<usize>().ok(), len)
+        };
+        if used == 0 || (used == 1 && after.starts_with('-')) {
+            let _ = out.push('!');
+            rest = after;
+            continue;
+        }
+        let token = &rest[pos..pos + 1 + used];
+        let entry = n.and_then(|n| shell.history_entry(n)).ok_or(token)?;
+        out.push_str(entry).map_err(|_| token)?;
+        rest = &after[used..];
+    }
+    out.push_str(rest).map_err(|_| line)?;
+    Ok(out)
+}
+
+/// Splits `<cmd> > <file>` or `<cmd> >> <file>` at the first `>` outside
+/// double quotes, returning the command, the target and whether to append.
+fn parse_redirect(line: &str) -> Option<(&str, &str, bool)> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '>' if !quoted => {
+                let rest = &line[i + 1..];
+                let (target, append) = match rest.strip_prefix('>') {
+                    Some(rest) => (rest, true),
+                    None => (rest, false),
+                };
+                return Some((line[..i].trim(), target.trim(), append));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `a; b; c` into its non-empty commands, or `None` if there are more
+/// than [`MAX_LINE_COMMANDS`]. `;` inside double quotes or inside an
+/// `if ...; then ...; fi` does not split.
+fn split_commands(line: &str) -> Option<heapless::Vec<&str, MAX_LINE_COMMANDS>> {
+    let mut commands = heapless::Vec::new();
+    let mut start = 0;
+    let mut chunk_start = 0;
+    let mut ifs = 0usize;
+    let mut quoted = false;
+    for (i, c) in line
+        .char_indices()
+        .chain(core::iter::once((line.len(), ';')))
+    {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted || i == line.len() => {
+                let chunk = line[chunk_start..i].trim();
+                if chunk.trim_start_matches("then ").starts_with("if ") {
+                    ifs += 1;
+                } else if chunk == "fi" {
+                    ifs = ifs.saturating_sub(1);
+                }
+                chunk_start = i + 1;
+                if ifs == 0 || i == line.len() {
+                    let command = line[start..i].trim();
+                    if !command.is_empty() {
+                        commands.push(command).ok()?;
+                    }
+                    start = i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(commands)
+}
+
+/// Splits `<cond>; then <body>; fi` (the part after `if `) into its commands.
+fn parse_if(rest: &str) -> Option<(&str, &str)> {
+    let (cond, tail) = rest.split_once("; then ")?;
+    let body = tail
+        .trim_end()
+        .strip_suffix("fi")?
+        .trim_end()
+        .strip_suffix(';')?;
+    Some((cond.trim(), body.trim()))
+}
+
+/// Recognises `name=value`, where `name` is an identifier. Double or single
+/// quotes around the value are dropped.
+fn parse_assignment(s: &str) -> Option<(&str, &str)> {
+    let (name, value) = s.split_once('=')?;
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    if !(first.is_ascii_alphabetic() || first == '_')
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    let value = value.trim();
+    let value = ['"', '\'']
+        .iter()
+        .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(value);
+    Some((name, value))
+}
+
+fn set_var(vars: &mut Vars, name: &str, value: &str) -> bool {
+    let mut val = heapless::String::new();
+    if val.push_str(value).is_err() {
+        return false;
+    }
+    if let Some(slot) = vars.iter_mut().find(|(n, _)| n == name) {
+        slot.1 = val;
+        return true;
+    }
+    let mut key = heapless::String::new();
+    key.push_str(name).is_ok() && vars.push((key, val)).is_ok()
+}
+
+/// Replaces `$name`, `${name}` and `$?` in `line`. Unset variables expand to nothing.
+fn expand_vars(line: &str, shell: &Shell) -> Option<heapless::String<256>> {
+    let mut out = heapless::String::<256>::new();
+    let mut rest = line;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]).ok()?;
+        let after = &rest[pos + 1..];
+        let (name, tail) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.split_once('}') {
+                Some((name, tail)) => (name, tail),
+                None => ("", after),
+            }
+        } else if let Some(tail) = after.strip_prefix('?') {
+            ("?", tail)
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            after.split_at(end)
+        };
+        match name {
+            "" => out.push('$').ok()?,
+            "?" => out.push(if shell.last_ok { '0' } else { '1' }).ok()?,
+            _ => {
+                if let Some((_, value)) = shell.vars.iter().find(|(n, _)| n == name) {
+                    out.push_str(value).ok()?;
+                }
+            }
+        }
+        rest = tail;
+    }
+    out.push_str(rest).ok()?;
+    Some(out)
+}
+
+/// Names of the programs `run` can launch, in registry order.
+pub fn program_names() -> impl Iterator<Item = &'static str> {
+    PROGRAMS.iter().map(|p| p.name)
+}
+
+/// The command or program name nearest to `name`, if it is within two edits
+/// and not a complete rewrite of a very short name.
+fn closest_name(name: &str) -> Option<&'static str> {
+    COMMAND_NAMES
+        .iter()
+        .copied()
+        .chain(program_names())
+        .map(|known| (edit_distance(name, known), known))
+        .filter(|&(d, _)| d <= 2 && d < name.chars().count())
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, known)| known)
+}
+
+fn list_programs() -> heapless::String<128> {
+    let mut s = heapless::String::<128>::new();
+    for (i, name) in program_names().enumerate() {
+        if i > 0 {
+            let _ = s.push_str(", ");
+        }
+        let _ = s.push_str(name);
+    }
+    s
+}
+
+/// Parses `<name> [count]` for `hexdump`; `None` means the arguments are bad.
+fn parse_hexdump_args(args: &str) -> Option<(&str, Option<usize>)> {
+    let mut parts = args.split_whitespace();
+    let name = parts.next()?;
+    let limit = match parts.next() {
+        Some(n) => Some(n.parse().ok()?),
+        None => None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((name, limit))
+}
+
+fn cmd_hexdump(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    cwd: &str,
+    args: &str,
+) -> ProgramResult {
+    let Some((name, limit)) = parse_hexdump_args(args) else {
+        kprintln!(st, "Usage: hexdump <name> [count]");
+        return ProgramResult::error("usage");
+    };
+    let Some(bytes) = read_relative(st, "hexdump", cwd, name) else {
+        return ProgramResult::error("read failed");
+    };
+    let len = limit.map_or(bytes.len(), |n| n.min(bytes.len()));
+    for (row, chunk) in bytes[..len].chunks(16).enumerate() {
+        let mut out = heapless::String::<80>::new();
+        let _ = write!(out, "{:08x} ", row * 16);
+        for i in 0..16 {
+            let _ = match chunk.get(i) {
+                Some(b) => write!(out, " {:02x}", b),
+                None => write!(out, "   "),
+            };
+        }
+        let _ = out.push_str("  |");
+        for &b in chunk {
+            let c = if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            };
+            let _ = out.push(c);
         }
-        line.clear();
-        read_line_shell(st, &mut line, &history, &mut hist_nav, cwd);
+        let _ = out.push('|');
+        outln!(st, sink, "{}", out);
+    }
+    outln!(st, sink, "{:08x}", len);
+    ProgramResult::Ok
+}
 
-        let s = line.trim();
-        if s.is_empty() {
-            continue;
+/// Reads `name` relative to `cwd`, reporting failures under `cmd`.
+fn read_relative(
+    st: &mut SystemTable<Boot>,
+    cmd: &str,
+    cwd: &str,
+    name: &str,
+) -> Option<alloc::vec::Vec<u8>> {
+    let Some(path) = join_path(cwd, name) else {
+        kprintln!(st, "{}: path too long", cmd);
+        return None;
+    };
+    match nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, &path)) {
+        Ok(bytes) => Some(bytes),
+        Err(e) => {
+            report_read_error(st, name, e);
+            None
         }
-        let (cmd_name, args) = match s.split_once(' ') {
-            Some((c, rest)) => (c, rest),
-            None => (s, ""),
-        };
+    }
+}
 
-        if cmd_name == "help" {
-            kprintln!(st, "Commands:");
-            for c in COMMANDS {
-                kprintln!(st, "  {:<12} {}", c.name, c.help);
-            }
-            continue;
+/// Splits file contents into lines on `\n`, dropping a trailing `\r`.
+/// A final newline does not start another line, and empty input has none.
+fn text_lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let body = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    (!bytes.is_empty())
+        .then(|| body.split(|&b| b == b'\n'))
+        .into_iter()
+        .flatten()
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// Prints one line of a text file, skipping bytes the console can't show.
+fn print_text_line(st: &mut SystemTable<Boot>, sink: &mut Sink, line: &[u8]) {
+    let mut out = console::out(st, sink);
+    for &b in line {
+        if b.is_ascii_graphic() || b == b' ' || b == b'\t' {
+            let _ = out.write_char(b as char);
         }
+    }
+    let _ = out.write_char('\n');
+}
 
-        match COMMANDS.iter().find(|c| c.name == cmd_name) {
-            Some(c) => (c.run)(st, args),
-            None => kprintln!(st, "Unknown: {} (try 'help')", cmd_name),
+/// Parses `[-n N] <name>` for `head` and `tail`.
+fn parse_line_count_args(args: &str) -> Option<(usize, &str)> {
+    let mut count = 10;
+    let mut name = None;
+    let mut parts = args.split_whitespace();
+    while let Some(arg) = parts.next() {
+        match arg {
+            "-n" => count = parts.next()?.parse().ok()?,
+            _ if arg.starts_with('-') => count = arg.strip_prefix("-n")?.parse().ok()?,
+            _ if name.is_none() => name = Some(arg),
+            _ => return None,
         }
+    }
+    Some((count, name?))
+}
+
+fn cmd_wc(st: &mut SystemTable<Boot>, sink: &mut Sink, cwd: &str, args: &str) -> ProgramResult {
+    let name = args.trim();
+    if name.is_empty() {
+        kprintln!(st, "Usage: wc <name>");
+        return ProgramResult::error("usage");
+    }
+    let Some(bytes) = read_relative(st, "wc", cwd, name) else {
+        return ProgramResult::error("read failed");
+    };
+    let lines = bytes.iter().filter(|&&b| b == b'\n').count();
+    let words = bytes
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|w| !w.is_empty())
+        .count();
+    outln!(st, sink, "{} {} {} {}", lines, words, bytes.len(), name);
+    ProgramResult::Ok
+}
+
+fn cmd_head(st: &mut SystemTable<Boot>, sink: &mut Sink, cwd: &str, args: &str) -> ProgramResult {
+    let Some((count, name)) = parse_line_count_args(args) else {
+        kprintln!(st, "Usage: head [-n N] <name>");
+        return ProgramResult::error("usage");
+    };
+    let Some(bytes) = read_relative(st, "head", cwd, name) else {
+        return ProgramResult::error("read failed");
+    };
+    for line in text_lines(&bytes).take(count) {
+        print_text_line(st, sink, line);
+    }
+    ProgramResult::Ok
+}
 
-        if history.last().map(|h| h.as_str()) != Some(s) {
-            let mut item = heapless::String::<256>::new();
-            let _ = item.push_str(s);
-            if history.len() == HISTORY_CAP {
-                let _ = history.remove(0);
+fn cmd_tail(st: &mut SystemTable<Boot>, sink: &mut Sink, cwd: &str, args: &str) -> ProgramResult {
+    let Some((count, name)) = parse_line_count_args(args) else {
+        kprintln!(st, "Usage: tail [-n N] <name>");
+        return ProgramResult::error("usage");
+    };
+    let Some(bytes) = read_relative(st, "tail", cwd, name) else {
+        return ProgramResult::error("read failed");
+    };
+    let total = text_lines(&bytes).count();
+    for line in text_lines(&bytes).skip(total.saturating_sub(count)) {
+        print_text_line(st, sink, line);
+    }
+    ProgramResult::Ok
+}
+
+/// Prints the numbered lines of a file that contain a literal pattern,
+/// cut at the screen edge so long lines don't wrap.
+fn cmd_grep(st: &mut SystemTable<Boot>, sink: &mut Sink, cwd: &str, args: &str) -> ProgramResult {
+    let mut ignore_case = false;
+    let mut words: Vec<&str, 2> = Vec::new();
+    for arg in args.split_whitespace() {
+        if arg == "-i" {
+            ignore_case = true;
+        } else if words.push(arg).is_err() {
+            words.clear();
+            break;
+        }
+    }
+    let [pattern, name] = words.as_slice() else {
+        kprintln!(st, "Usage: grep [-i] <pattern> <name>");
+        return ProgramResult::error("usage");
+    };
+    let Some(bytes) = read_relative(st, "grep", cwd, name) else {
+        return ProgramResult::error("read failed");
+    };
+    let pattern = pattern.as_bytes();
+    let matches = |line: &[u8]| {
+        line.windows(pattern.len()).any(|w| {
+            if ignore_case {
+                w.eq_ignore_ascii_case(pattern)
+            } else {
+                w == pattern
             }
-            let _ = history.push(item);
+        })
+    };
+    for (n, line) in text_lines(&bytes).enumerate() {
+        if !matches(line) {
+            continue;
         }
-        hist_nav = None;
+        let mut out = heapless::String::<{ vga::BUFFER_WIDTH }>::new();
+        let _ = write!(out, "{:>4}: ", n + 1);
+        // Leave the last column free so a full row doesn't wrap onto the next.
+        for &b in line.iter().take(vga::BUFFER_WIDTH - 1 - out.len()) {
+            let c = match b {
+                b'\t' => ' ',
+                _ if b.is_ascii_graphic() || b == b' ' => b as char,
+                _ => '.',
+            };
+            let _ = out.push(c);
+        }
+        outln!(st, sink, "{}", out);
     }
+    ProgramResult::Ok
 }
 
-fn list_programs() -> heapless::String<128> {
-    let mut s = heapless::String::<128>::new();
-    for (i, p) in PROGRAMS.iter().enumerate() {
-        if i > 0 {
-            let _ = s.push_str(", ");
+fn report_read_error(st: &mut SystemTable<Boot>, name: &str, err: NoriError) {
+    match err {
+        NoriError::NoFs => kprintln!(st, "No filesystem available"),
+        NoriError::NotFound => kprintln!(st, "{}: no such file or directory", name),
+        NoriError::IsDirectory => kprintln!(st, "{}: is a directory", name),
+        NoriError::Read => kprintln!(st, "{}: read error", name),
+        NoriError::Write => kprintln!(st, "{}: write error", name),
+        NoriError::AlreadyExists => kprintln!(st, "{}: already exists", name),
+        NoriError::DirectoryNotEmpty => kprintln!(st, "{}: directory not empty", name),
+    }
+}
+
+/// Seconds since 1970-01-01 for a firmware timestamp, ignoring the timezone.
+pub(crate) fn epoch_secs(t: &uefi::table::runtime::Time) -> i64 {
+    let (y, m, d) = (t.year() as i64, t.month() as i64, t.day() as i64);
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    days * 86_400 + t.hour() as i64 * 3600 + t.minute() as i64 * 60 + t.second() as i64
+}
+
+fn split_first_arg(s: &str) -> (&str, &str) {
+    if let Some(quote) = s.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        if let Some(end) = s[1..].find(quote) {
+            return (&s[1..end + 1], &s[end + 2..]);
         }
-        let _ = s.push_str(p.name);
     }
-    s
+    match s.split_once(' ') {
+        Some((first, rest)) => (first, rest),
+        None => (s, ""),
+    }
 }
 
 fn find_program(name: &str) -> Option<&'static ProgramEntry> {
     PROGRAMS.iter().find(|p| p.name == name)
 }
 
-fn read_line_simple(st: &mut SystemTable<Boot>, buf: &mut heapless::String<256>) {
-    let _ = st.stdout().enable_cursor(true);
+/// Reads a line into `buf`. Returns `false` if Ctrl+D was pressed on an empty line.
+fn read_line_simple(st: &mut SystemTable<Boot>, buf: &mut heapless::String<256>) -> bool {
+    let _ = console::stdout(st).enable_cursor(true);
     loop {
         let read_result = {
             let stdin: &mut Input = st.stdin();
@@ -347,17 +2378,28 @@ fn read_line_simple(st: &mut SystemTable<Boot>, buf: &mut heapless::String<256>)
                     match c {
                         '\r' | '\n' => {
                             kprintln!(st, "");
-                            return;
+                            return true;
+                        }
+                        EOT => {
+                            if buf.is_empty() {
+                                kprintln!(st, "");
+                                return false;
+                            }
+                        }
+                        CTRL_C => {
+                            kprintln!(st, "^C");
+                            buf.clear();
+                            return true;
                         }
                         '\u{8}' => {
                             if !buf.is_empty() {
                                 buf.pop();
-                                let _ = write!(st.stdout(), "\u{8} \u{8}");
+                                let _ = write!(console::stdout(st), "\u{8} \u{8}");
                             }
                         }
                         _ => {
                             if buf.push(c).is_ok() {
-                                let _ = write!(st.stdout(), "{}", c);
+                                let _ = write!(console::stdout(st), "{}", c);
                             }
                         }
                     }
@@ -379,16 +2421,75 @@ fn read_line_simple(st: &mut SystemTable<Boot>, buf: &mut heapless::String<256>)
     }
 }
 
+/// Sets the text color on UEFI stdout and the VGA writer, whichever is showing.
+fn set_text_color(st: &mut SystemTable<Boot>, fg: Rgb, bg: Rgb) {
+    vga::set_color(fg.to_vga(), bg.to_vga());
+    let _ = console::stdout(st).set_color(fg, bg);
+}
+
+/// Goes back to the theme colors after a highlight.
+fn reset_text_color(st: &mut SystemTable<Boot>) {
+    let (fg, bg) = console::theme();
+    set_text_color(st, fg, bg);
+}
+
+/// Makes the `theme` setting the normal text colors.
+fn apply_theme(st: &mut SystemTable<Boot>, config: &Config) {
+    if let Some((fg, bg)) = config::parse_theme(&config.theme) {
+        console::set_theme(fg, bg);
+        set_text_color(st, fg, bg);
+    }
+}
+
+/// There is nothing to return to once the shell is gone, so power off.
+fn exit_shell(st: &mut SystemTable<Boot>) -> ! {
+    kprintln!(st, "exit");
+    st.runtime_services()
+        .reset(ResetType::SHUTDOWN, Status::SUCCESS, None)
+}
+
+/// Asks a yes/no question and waits for a single key. Enter picks `default`.
+fn confirm(st: &mut SystemTable<Boot>, prompt: &str, default: bool) -> bool {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    let _ = write!(console::stdout(st), "{} {} ", prompt, hint);
+    let answer = loop {
+        let read_result = { st.stdin().read_key() };
+        match read_result {
+            Ok(Some(Key::Printable(c16))) => match char::from(c16) {
+                'y' | 'Y' => break true,
+                'n' | 'N' => break false,
+                '\r' | '\n' => break default,
+                _ => {}
+            },
+            Ok(Some(Key::Special(ScanCode::ESCAPE))) => break false,
+            Ok(_) => st.boot_services().stall(1000),
+            Err(_) => st.boot_services().stall(2000),
+        }
+    };
+    kprintln!(st, "{}", if answer { "y" } else { "n" });
+    answer
+}
+
+/// Line editor for the shell prompt. Returns `false` on Ctrl+D at an empty line.
 fn read_line_shell(
     st: &mut SystemTable<Boot>,
     buf: &mut heapless::String<256>,
-    history: &heapless::Vec<heapless::String<256>, 32>,
+    history: &History,
     hist_nav: &mut Option<usize>,
+    prompt: &str,
     cwd: &str,
-) {
-    let _ = st.stdout().enable_cursor(true);
+) -> bool {
+    let _ = console::stdout(st).enable_cursor(true);
+    let mut idle_ms: u64 = 0;
+    // Byte offset of the cursor in `buf`; edits happen here, not at the end.
+    let mut cursor = buf.len();
     loop {
         let read_result = { st.stdin().read_key() };
+        if let Ok(Some(key)) = &read_result {
+            if !matches!(key, Key::Special(ScanCode::PAGE_UP | ScanCode::PAGE_DOWN)) {
+                console::end_scroll(st);
+            }
+        }
         match read_result {
             Ok(Some(key)) => match key {
                 Key::Printable(c16) => {
@@ -396,20 +2497,61 @@ fn read_line_shell(
                     match c {
                         '\r' | '\n' => {
                             kprintln!(st, "");
-                            return;
+                            return true;
+                        }
+                        EOT => {
+                            if buf.is_empty() {
+                                kprintln!(st, "");
+                                return false;
+                            }
+                        }
+                        CTRL_C => {
+                            kprintln!(st, "^C");
+                            buf.clear();
+                            *hist_nav = None;
+                            return true;
                         }
                         '\u{8}' => {
-                            if !buf.is_empty() {
-                                buf.pop();
-                                let _ = write!(st.stdout(), "\u{8} \u{8}");
+                            let Some(prev) = buf[..cursor].chars().next_back() else {
+                                continue;
+                            };
+                            let start = cursor - prev.len_utf8();
+                            let mut next = heapless::String::<256>::new();
+                            let _ = next.push_str(&buf[..start]);
+                            let _ = next.push_str(&buf[cursor..]);
+                            *buf = next;
+                            cursor = start;
+                            let tail = &buf[cursor..];
+                            let _ = write!(console::stdout(st), "\u{8}{} ", tail);
+                            back_up(st, tail.chars().count() + 1);
+                        }
+                        CTRL_R => {
+                            move_to_end(st, buf, &mut cursor);
+                            if let Some(found) = reverse_search(st, history, prompt, buf) {
+                                *buf = found;
+                                kprintln!(st, "");
+                                return true;
                             }
+                            cursor = buf.len();
                         }
+                        CTRL_A => move_to_start(st, buf, &mut cursor),
+                        CTRL_E => move_to_end(st, buf, &mut cursor),
                         '\t' => {
-                            autocomplete_line(st, buf, cwd);
+                            move_to_end(st, buf, &mut cursor);
+                            autocomplete_line(st, buf, prompt, cwd);
+                            cursor = buf.len();
                         }
                         _ => {
-                            if buf.push(c).is_ok() {
-                                let _ = write!(st.stdout(), "{}", c);
+                            let mut next = heapless::String::<256>::new();
+                            let inserted = next.push_str(&buf[..cursor]).is_ok()
+                                && next.push(c).is_ok()
+                                && next.push_str(&buf[cursor..]).is_ok();
+                            if inserted {
+                                *buf = next;
+                                cursor += c.len_utf8();
+                                let tail = &buf[cursor..];
+                                let _ = write!(console::stdout(st), "{}{}", c, tail);
+                                back_up(st, tail.chars().count());
                             }
                         }
                     }
@@ -428,13 +2570,17 @@ fn read_line_shell(
                             continue;
                         }
                         *hist_nav = Some(idx);
-                        let s = &history[history.len() - 1 - idx];
+                        let Some(s) = history.get(idx) else {
+                            continue;
+                        };
+                        move_to_end(st, buf, &mut cursor);
                         for _ in 0..buf.len() {
-                            let _ = write!(st.stdout(), "\u{8} \u{8}");
+                            let _ = write!(console::stdout(st), "\u{8} \u{8}");
                         }
                         buf.clear();
                         let _ = buf.push_str(s);
-                        let _ = write!(st.stdout(), "{}", s);
+                        let _ = write!(console::stdout(st), "{}", s);
+                        cursor = buf.len();
                     }
                     ScanCode::DOWN => {
                         if history.is_empty() {
@@ -444,39 +2590,175 @@ fn read_line_shell(
                             None => {}
                             Some(0) => {
                                 *hist_nav = None;
+                                move_to_end(st, buf, &mut cursor);
                                 for _ in 0..buf.len() {
-                                    let _ = write!(st.stdout(), "\u{8} \u{8}");
+                                    let _ = write!(console::stdout(st), "\u{8} \u{8}");
                                 }
                                 buf.clear();
+                                cursor = 0;
                             }
                             Some(i) => {
                                 let ni = i - 1;
                                 *hist_nav = Some(ni);
-                                let s = &history[history.len() - 1 - ni];
+                                let Some(s) = history.get(ni) else {
+                                    continue;
+                                };
+                                move_to_end(st, buf, &mut cursor);
                                 for _ in 0..buf.len() {
-                                    let _ = write!(st.stdout(), "\u{8} \u{8}");
+                                    let _ = write!(console::stdout(st), "\u{8} \u{8}");
                                 }
                                 buf.clear();
                                 let _ = buf.push_str(s);
-                                let _ = write!(st.stdout(), "{}", s);
+                                let _ = write!(console::stdout(st), "{}", s);
+                                cursor = buf.len();
                             }
                         }
                     }
-                    ScanCode::LEFT | ScanCode::RIGHT => {}
+                    ScanCode::PAGE_UP => console::scroll(st, 1),
+                    ScanCode::PAGE_DOWN => console::scroll(st, -1),
+                    ScanCode::HOME => move_to_start(st, buf, &mut cursor),
+                    ScanCode::END => move_to_end(st, buf, &mut cursor),
+                    ScanCode::LEFT => {
+                        if let Some(prev) = buf[..cursor].chars().next_back() {
+                            cursor -= prev.len_utf8();
+                            back_up(st, 1);
+                        }
+                    }
+                    ScanCode::RIGHT => {
+                        if let Some(next) = buf[cursor..].chars().next() {
+                            cursor += next.len_utf8();
+                            let _ = write!(console::stdout(st), "{}", next);
+                        }
+                    }
                     _ => {}
                 },
             },
             Ok(None) => {
-                let _ = st.boot_services().stall(1000);
+                st.boot_services().stall(1000);
+                idle_ms += 1;
+                tick_vga_cursor(idle_ms);
             }
             Err(_) => {
-                let _ = st.boot_services().stall(2000);
+                st.boot_services().stall(2000);
+                idle_ms += 2;
+                tick_vga_cursor(idle_ms);
             }
         }
     }
 }
 
-fn autocomplete_line(st: &mut SystemTable<Boot>, buf: &mut heapless::String<256>, cwd: &str) {
+/// Blinks the VGA cursor, but only while VGA is the screen in use: under the
+/// firmware console or GOP the text buffer isn't ours to touch.
+fn tick_vga_cursor(idle_ms: u64) {
+    if log::vga_in_use() {
+        vga::tick_cursor(idle_ms);
+    }
+}
+
+/// Ctrl-R incremental search: typing narrows to the newest history entry
+/// containing the query, Ctrl-R again steps to older matches. Enter returns
+/// the match; Escape returns `None` and puts `prompt` and `buf` back.
+fn reverse_search(
+    st: &mut SystemTable<Boot>,
+    history: &History,
+    prompt: &str,
+    buf: &str,
+) -> Option<heapless::String<256>> {
+    let find = |query: &str, from: usize| {
+        (from..history.len()).find(|&i| history.get(i).is_some_and(|h| h.contains(query)))
+    };
+    let mut query = heapless::String::<64>::new();
+    // Age of the oldest entry searched from; a new query restarts at the newest.
+    let mut age = 0;
+    let mut drawn = prompt.chars().count() + buf.chars().count();
+    let mut keys = KeyReader::new();
+    loop {
+        let found = find(&query, age);
+        let shown = found
+            .and_then(|i| history.get(i))
+            .map_or("", |h| h.as_str());
+        let mut line = heapless::String::<384>::new();
+        let _ = write!(line, "(reverse-i-search)`{}': {}", query, shown);
+        redraw_line(st, &line, &mut drawn);
+
+        match keys.read(st) {
+            Key::Printable(c16) => match char::from(c16) {
+                '\r' | '\n' => {
+                    let mut line = heapless::String::<512>::new();
+                    let _ = write!(line, "{}{}", prompt, shown);
+                    redraw_line(st, &line, &mut drawn);
+                    let mut out = heapless::String::new();
+                    let _ = out.push_str(shown);
+                    return Some(out);
+                }
+                CTRL_R => {
+                    if let Some(older) = found.and_then(|i| find(&query, i + 1)) {
+                        age = older;
+                    }
+                }
+                '\u{8}' => {
+                    query.pop();
+                    age = 0;
+                }
+                '\u{1b}' => break,
+                c if !c.is_control() => {
+                    let _ = query.push(c);
+                    age = 0;
+                }
+                _ => {}
+            },
+            Key::Special(ScanCode::ESCAPE) => break,
+            Key::Special(_) => {}
+        }
+    }
+    let mut line = heapless::String::<512>::new();
+    let _ = write!(line, "{}{}", prompt, buf);
+    redraw_line(st, &line, &mut drawn);
+    None
+}
+
+/// Rewrites the current screen line with `text`, blanking whatever was left
+/// over from the previous `drawn` characters.
+fn redraw_line(st: &mut SystemTable<Boot>, text: &str, drawn: &mut usize) {
+    let len = text.chars().count();
+    let pad = drawn.saturating_sub(len);
+    {
+        let mut out = console::stdout(st);
+        let _ = write!(out, "\r{}", text);
+        for _ in 0..pad {
+            let _ = out.write_char(' ');
+        }
+    }
+    back_up(st, pad);
+    *drawn = len;
+}
+
+/// Moves the on-screen cursor `n` characters left without erasing.
+fn back_up(st: &mut SystemTable<Boot>, n: usize) {
+    let mut out = console::stdout(st);
+    for _ in 0..n {
+        let _ = out.write_char('\u{8}');
+    }
+}
+
+/// Moves the cursor back to the start of `buf`.
+fn move_to_start(st: &mut SystemTable<Boot>, buf: &str, cursor: &mut usize) {
+    back_up(st, buf[..*cursor].chars().count());
+    *cursor = 0;
+}
+
+/// Reprints the part of `buf` after `cursor` so the cursor ends up at the end.
+fn move_to_end(st: &mut SystemTable<Boot>, buf: &str, cursor: &mut usize) {
+    let _ = write!(console::stdout(st), "{}", &buf[*cursor..]);
+    *cursor = buf.len();
+}
+
+fn autocomplete_line(
+    st: &mut SystemTable<Boot>,
+    buf: &mut heapless::String<256>,
+    prompt: &str,
+    cwd: &str,
+) {
     let mut snapshot = heapless::String::<256>::new();
     let _ = snapshot.push_str(buf.as_str());
     let s = snapshot.as_str();
@@ -487,40 +2769,82 @@ fn autocomplete_line(st: &mut SystemTable<Boot>, buf: &mut heapless::String<256>
         None => (s, None),
     };
 
-    if tail.is_none() {
-        for &name in COMMAND_NAMES {
-            let _ = candidates.push(name);
-        }
-        for p in PROGRAMS {
-            let _ = candidates.push(p.name);
+    match tail {
+        None => {
+            for &name in COMMAND_NAMES {
+                let _ = candidates.push(name);
+            }
+            for p in PROGRAMS {
+                let _ = candidates.push(p.name);
+            }
+            complete_from_set(st, buf, head, candidates.as_slice(), None, prompt);
         }
-        complete_from_set(st, buf, head, candidates.as_slice(), None, cwd);
-    } else {
-        if head == "run" {
+        Some(tail) if head == "run" => {
             for p in PROGRAMS {
                 let _ = candidates.push(p.name);
             }
-            complete_from_set(
-                st,
-                buf,
-                tail.unwrap(),
-                candidates.as_slice(),
-                Some("run "),
-                cwd,
-            );
+            complete_from_set(st, buf, tail, candidates.as_slice(), Some("run "), prompt);
+        }
+        Some(tail) => {
+            let fragment = tail.rsplit(' ').next().unwrap_or("");
+            if !fragment.starts_with('-') {
+                if PATH_COMMANDS.contains(&head) {
+                    complete_path(st, buf, s, fragment, prompt, cwd);
+                }
+                return;
+            }
+            let Some((_, flags)) = COMMAND_FLAGS.iter().find(|(name, _)| *name == head) else {
+                return;
+            };
+            let prefix = &s[..s.len() - fragment.len()];
+            complete_from_set(st, buf, fragment, flags, Some(prefix), prompt);
         }
     }
 }
 
-fn complete_from_set(
+/// Completes `fragment`, the last word of `line`, against the entries of the
+/// directory it names (relative to `cwd`). Directories get a trailing `/`.
+fn complete_path(
     st: &mut SystemTable<Boot>,
     buf: &mut heapless::String<256>,
+    line: &str,
     fragment: &str,
-    set: &[&'static str],
-    prefix: Option<&'static str>,
+    prompt: &str,
     cwd: &str,
 ) {
-    let mut matches: heapless::Vec<&'static str, 32> = heapless::Vec::new();
+    let (dir, name) = match fragment.rfind('/') {
+        Some(i) => (&fragment[..=i], &fragment[i + 1..]),
+        None => (".", fragment),
+    };
+    let Some(dir) = join_path(cwd, dir) else {
+        return;
+    };
+    let mut entries: heapless::Vec<heapless::String<64>, 32> = heapless::Vec::new();
+    let listed = nori::list_dir_info(st, &dir, |info| {
+        let mut entry = heapless::String::<64>::new();
+        let _ = write!(entry, "{}", info.file_name());
+        if info.is_directory() {
+            let _ = entry.push('/');
+        }
+        let _ = entries.push(entry);
+    });
+    if listed.is_err() {
+        return;
+    }
+    let names: heapless::Vec<&str, 32> = entries.iter().map(|e| e.as_str()).collect();
+    let prefix = &line[..line.len() - name.len()];
+    complete_from_set(st, buf, name, &names, Some(prefix), prompt);
+}
+
+fn complete_from_set(
+    st: &mut SystemTable<Boot>,
+    buf: &mut heapless::String<256>,
+    fragment: &str,
+    set: &[&str],
+    prefix: Option<&str>,
+    prompt: &str,
+) {
+    let mut matches: heapless::Vec<&str, 32> = heapless::Vec::new();
     for name in set.iter().copied() {
         if name.starts_with(fragment) {
             let _ = matches.push(name);
@@ -532,126 +2856,322 @@ fn complete_from_set(
     }
     if matches.len() == 1 {
         for _ in 0..buf.len() {
-            let _ = write!(st.stdout(), "\u{8} \u{8}");
+            let _ = write!(console::stdout(st), "\u{8} \u{8}");
         }
         buf.clear();
         if let Some(p) = prefix {
             let _ = buf.push_str(p);
         }
         let _ = buf.push_str(matches[0]);
-        let _ = write!(st.stdout(), "{}", buf.as_str());
+        let _ = write!(console::stdout(st), "{}", buf.as_str());
         return;
     }
 
     kprintln!(st, "");
     for (i, m) in matches.iter().enumerate() {
         if i > 0 {
-            let _ = write!(st.stdout(), " ");
+            let _ = write!(console::stdout(st), " ");
         }
-        let _ = write!(st.stdout(), "{}", m);
+        let _ = write!(console::stdout(st), "{}", m);
     }
     kprintln!(st, "");
-    let _ = write!(st.stdout(), "{}{}", cwd, PREFIX);
-    let _ = write!(st.stdout(), "{}", buf.as_str());
+    let _ = write!(console::stdout(st), "{}{}", prompt, buf.as_str());
 }
 
-fn echo_program(st: &mut SystemTable<Boot>) {
-    let out = st.stdout();
-    let _ = out.clear();
+fn echo_program(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    _cwd: &str,
+    args: &str,
+) -> ProgramResult {
+    if !args.is_empty() {
+        outln!(st, sink, "{}", args);
+        return ProgramResult::Ok;
+    }
+    let _ = console::stdout(st).clear();
     kprintln!(st, "Echo program. Type 'exit' to return.");
     let mut line = heapless::String::<256>::new();
     loop {
-        let _ = write!(st.stdout(), "echo {} ", PREFIX);
+        let _ = write!(console::stdout(st), "echo {} ", PREFIX);
         line.clear();
-        read_line_simple(st, &mut line);
+        if !read_line_simple(st, &mut line) {
+            return ProgramResult::Exit;
+        }
         let s = line.trim();
         if s == "exit" {
-            break;
+            return ProgramResult::Exit;
         }
         kprintln!(st, "{}", s);
     }
 }
 
-fn keys_program(st: &mut SystemTable<Boot>) {
-    let out = st.stdout();
-    let _ = out.clear();
+fn hexdump_program(
+    st: &mut SystemTable<Boot>,
+    sink: &mut Sink,
+    cwd: &str,
+    args: &str,
+) -> ProgramResult {
+    if !args.is_empty() {
+        return cmd_hexdump(st, sink, cwd, args);
+    }
+    let _ = console::stdout(st).clear();
+    kprintln!(st, "Hexdump. Enter '<file> [count]', or 'exit' to return.");
+    let mut line = heapless::String::<256>::new();
+    loop {
+        let _ = write!(console::stdout(st), "hexdump {} ", PREFIX);
+        line.clear();
+        if !read_line_simple(st, &mut line) {
+            return ProgramResult::Exit;
+        }
+        match line.trim() {
+            "exit" => return ProgramResult::Exit,
+            "" => {}
+            args => {
+                let _ = cmd_hexdump(st, &mut Sink::Console, cwd, args);
+            }
+        }
+    }
+}
+
+fn keys_program(
+    st: &mut SystemTable<Boot>,
+    _sink: &mut Sink,
+    _cwd: &str,
+    _args: &str,
+) -> ProgramResult {
+    let _ = console::stdout(st).clear();
     kprintln!(st, "Keys demo. Press ESC to return.");
 
     {
         let stdin = st.stdin();
         let _ = stdin.reset(false);
     }
+    let mut keys = KeyReader::new();
     loop {
-        let read_result = { st.stdin().read_key() };
-        match read_result {
-            Ok(Some(Key::Printable(c16))) => {
+        match keys.read(st) {
+            Key::Printable(c16) => {
                 let c: char = c16.into();
                 kprintln!(st, "Printable: {:?}", c);
-                if c == '\u{1b}' {
-                    break;
+                if c == '\u{1b}' || c == EOT {
+                    return ProgramResult::Exit;
                 }
             }
-            Ok(Some(Key::Special(sc))) => {
-                kprintln!(st, "Special: {:?}", sc);
+            Key::Special(sc) => {
+                if keys.held() > 0 {
+                    kprintln!(st, "Special: {:?} (held x{})", sc, keys.held() + 1);
+                } else {
+                    kprintln!(st, "Special: {:?}", sc);
+                }
                 if sc == ScanCode::ESCAPE {
-                    break;
+                    return ProgramResult::Exit;
                 }
             }
-            Ok(None) => {
-                let _ = st.boot_services().stall(1_000);
-            }
-            Err(_) => {
-                let _ = st.boot_services().stall(2_000);
-            }
         }
     }
 }
 
-fn glow_program(st: &mut SystemTable<Boot>) {
-    let out = st.stdout();
-    let _ = out.clear();
-    kprintln!(st, "glow — neovim real no clickbait");
-    kprintln!(st, "Type text. Commands: :q to quit.");
+/// Lines the glow buffer holds.
+const GLOW_MAX_LINES: usize = 64;
+/// Buffer lines glow shows at once; older ones scroll off the top.
+const GLOW_VISIBLE: usize = 18;
+
+type GlowBuffer = Vec<heapless::String<256>, GLOW_MAX_LINES>;
+
+/// A minimal always-inserting line editor. Each entered line is appended to
+/// the buffer; `:w`, `:wq`, `:q` and `:q!` save and quit.
+fn glow_program(
+    st: &mut SystemTable<Boot>,
+    _sink: &mut Sink,
+    cwd: &str,
+    args: &str,
+) -> ProgramResult {
+    let mut lines = GlowBuffer::new();
+    let mut file: Option<heapless::String<256>> = None;
+    if !args.is_empty() {
+        let Some(path) = join_path(cwd, args) else {
+            kprintln!(st, "glow: path too long");
+            return ProgramResult::error("path too long");
+        };
+        match nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, &path)) {
+            Ok(bytes) => {
+                // Saving writes the buffer back as is, so refuse anything it
+                // can't hold exactly rather than mangle it on `:w`.
+                for text in text_lines(&bytes) {
+                    let Ok(text) = core::str::from_utf8(text) else {
+                        kprintln!(st, "glow: {} is not UTF-8 text", args);
+                        return ProgramResult::error("not UTF-8");
+                    };
+                    let mut line = heapless::String::new();
+                    if line.push_str(text).is_err() || lines.push(line).is_err() {
+                        kprintln!(
+                            st,
+                            "glow: {} is too big (at most {} lines of 256 bytes)",
+                            args,
+                            GLOW_MAX_LINES
+                        );
+                        return ProgramResult::error("file too big");
+                    }
+                }
+            }
+            Err(NoriError::NotFound) => {}
+            Err(e) => {
+                report_read_error(st, args, e);
+                return ProgramResult::error("read failed");
+            }
+        }
+        file = Some(path);
+    }
     {
         let stdin = st.stdin();
         let _ = stdin.reset(false);
     }
 
+    let mut dirty = false;
     let mut line = heapless::String::<256>::new();
+    glow_redraw(st, &lines, file.as_deref());
     loop {
         line.clear();
-        let _ = write!(st.stdout(), "> ");
-        read_line_simple(st, &mut line);
-        let s = line.as_str();
-        if s.starts_with(':') {
-            let cmd = &s[1..].trim();
-            match *cmd {
-                "q" | "quit" => break,
-                _ => {
-                    kprintln!(st, "Unknown command: :{}", cmd);
+        let _ = write!(console::stdout(st), "> ");
+        if !read_line_simple(st, &mut line) {
+            return ProgramResult::Exit;
+        }
+        let Some(cmd) = line.strip_prefix(':') else {
+            if lines.push(line.clone()).is_err() {
+                kprintln!(st, "Buffer full ({} lines)", GLOW_MAX_LINES);
+                continue;
+            }
+            dirty = true;
+            glow_redraw(st, &lines, file.as_deref());
+            continue;
+        };
+        let (cmd, arg) = cmd.trim().split_once(' ').unwrap_or((cmd.trim(), ""));
+        match cmd {
+            "q" | "quit" if dirty => {
+                kprintln!(st, "Unsaved changes (:w to save, :q! to discard)");
+            }
+            "q" | "quit" | "q!" => return ProgramResult::Exit,
+            "w" | "wq" => {
+                let arg = arg.trim();
+                if !arg.is_empty() {
+                    file = join_path(cwd, arg);
+                }
+                let Some(path) = file.as_deref() else {
+                    kprintln!(st, "No file name (use :w <file>)");
+                    continue;
+                };
+                if glow_save(st, &lines, path) {
+                    dirty = false;
+                    if cmd == "wq" {
+                        return ProgramResult::Exit;
+                    }
                 }
             }
-        } else {
-            kprintln!(st, "{}", s);
+            _ => {
+                kprintln!(st, "Unknown command: :{}", cmd);
+            }
+        }
+    }
+}
+
+fn glow_redraw(st: &mut SystemTable<Boot>, lines: &GlowBuffer, file: Option<&str>) {
+    let _ = console::stdout(st).clear();
+    kprintln!(st, "glow — {}", file.unwrap_or("[new file]"));
+    kprintln!(st, "Type text. Commands: :w [file], :wq, :q, :q!");
+    let first = lines.len().saturating_sub(GLOW_VISIBLE);
+    for (i, text) in lines.iter().enumerate().skip(first) {
+        kprintln!(st, "{:>3} {}", i + 1, text);
+    }
+}
+
+/// Writes the buffer to `path`, one `\n`-terminated line each, through a
+/// temp file so a failed write leaves the old contents in place.
+fn glow_save(st: &mut SystemTable<Boot>, lines: &GlowBuffer, path: &str) -> bool {
+    let mut text = alloc::string::String::new();
+    for line in lines.iter() {
+        text.push_str(line);
+        text.push('\n');
+    }
+    let saved = nori::with_boot_fs(st.boot_services(), |fs| {
+        if fs.stat(path).is_ok_and(|entry| entry.is_dir) {
+            return Err(NoriError::IsDirectory);
+        }
+        nori::write_file_atomic(fs, path, text.as_bytes()).map_err(|e| match e.status() {
+            Status::NOT_FOUND | Status::INVALID_PARAMETER => NoriError::NotFound,
+            _ => NoriError::Write,
+        })
+    });
+    match saved {
+        Ok(()) => {
+            kprintln!(st, "Wrote {} lines to {}", lines.len(), path);
+            true
+        }
+        Err(e) => {
+            report_read_error(st, path, e);
+            false
+        }
+    }
+}
+
+fn plasma_program(
+    st: &mut SystemTable<Boot>,
+    _sink: &mut Sink,
+    _cwd: &str,
+    _args: &str,
+) -> ProgramResult {
+    let _ = console::stdout(st).clear();
+    let _ = st.stdin().reset(false);
+
+    let mut area = wasabi::Rect::new(0, 0, 0, 0);
+    let gop = wasabi::with_gop(st.boot_services(), |gop| {
+        let (w, h) = (wasabi::width(gop), wasabi::height(gop));
+        area = wasabi::Rect::new(w / 4, h / 4, w / 2, h / 2);
+    });
+    if gop.is_err() {
+        return ProgramResult::error("no graphics output available");
+    }
+
+    let mut keys = KeyReader::new();
+    let mut t: u32 = 0;
+    loop {
+        let _ = wasabi::with_gop(st.boot_services(), |gop| wasabi::fill_plasma(gop, area, t));
+        t = t.wrapping_add(2);
+        if keys.poll(st).is_some() {
+            let _ = console::stdout(st).clear();
+            return ProgramResult::Exit;
         }
     }
 }
 
-fn zam_program(st: &mut SystemTable<Boot>) {
-    let _ = st.stdout().clear();
+fn zam_program(
+    st: &mut SystemTable<Boot>,
+    _sink: &mut Sink,
+    _cwd: &str,
+    _args: &str,
+) -> ProgramResult {
+    let _ = console::stdout(st).clear();
     let _ = st.stdin().reset(false);
-    let _ = st.stdout().enable_cursor(true);
-
-    let mut screen_w = 0usize;
-    let mut screen_h = 0usize;
-    let mut cell_w = 8usize;
-    let mut cell_h = 16usize;
-    let _ = wasabi::with_gop(st.boot_services(), |gop| {
-        screen_w = wasabi::width(gop);
-        screen_h = wasabi::height(gop);
-        cell_w = screen_w / 80usize;
-        cell_h = screen_h / 25usize;
+    let _ = console::stdout(st).enable_cursor(false);
+
+    let (cell_w, cell_h) = (
+        wasabi::font::DEFAULT.advance(),
+        wasabi::font::DEFAULT.height(),
+    );
+    let (mut desk_bg, mut win_bg, mut title_bg, mut text_fg) = (0, 0, 0, 0);
+    let (mut pointer, mut pointer_edge) = (0, 0);
+    let display = wasabi::with_gop(st.boot_services(), |gop| {
+        desk_bg = wasabi::to_color_for(gop, 48, 25, 52);
+        win_bg = wasabi::to_color_for(gop, 30, 20, 32);
+        title_bg = wasabi::to_color_for(gop, 40, 30, 42);
+        text_fg = wasabi::to_color_for(gop, 220, 220, 220);
+        pointer = wasabi::to_color_for(gop, 255, 255, 255);
+        pointer_edge = wasabi::to_color_for(gop, 0, 0, 0);
+        wasabi::Display::new(gop)
     });
+    let Ok(mut display) = display else {
+        return ProgramResult::error("no graphics output available");
+    };
+    let screen_w = display.info().width;
+    let screen_h = display.info().height;
 
     let mut win_w = (screen_w * 3) / 5;
     let mut win_h = (screen_h * 3) / 5;
@@ -664,12 +3184,20 @@ fn zam_program(st: &mut SystemTable<Boot>) {
     let mut win_x = screen_w / 6;
     let mut win_y = screen_h / 6;
     let title_h = cell_h;
+    let cols = (win_w - 2 * cell_w) / cell_w;
+    let rows = (win_h - title_h - cell_h) / cell_h;
+    // Top-left pixel of a text cell inside the window.
+    let cell = |win_x: usize, win_y: usize, col: usize, row: usize| {
+        (
+            win_x + cell_w * (col + 1),
+            win_y + title_h + cell_h / 2 + cell_h * row,
+        )
+    };
 
     let mut line = heapless::String::<256>::new();
     let mut cur_row = 0usize;
     let mut mouse_x = (screen_w / 2) as i32;
     let mut mouse_y = (screen_h / 2) as i32;
-    let mut last_px: Option<(usize, usize)> = None;
     let mut dragging = false;
     let mut prev_left = false;
 
@@ -685,43 +3213,27 @@ fn zam_program(st: &mut SystemTable<Boot>) {
         }
     }
 
+    let mut cursor = wasabi::Cursor::new(pointer, pointer_edge);
+    let mut keys = KeyReader::new();
     let mut redraw_window = true;
+    let mut pointer_moved = false;
     loop {
         if redraw_window {
-            let _ = wasabi::with_gop(st.boot_services(), |gop| {
-                wasabi::fill_rect(gop, 0, 0, screen_w, screen_h, wasabi::to_color(48, 25, 52));
-                wasabi::fill_rect(
-                    gop,
-                    win_x,
-                    win_y,
-                    win_w,
-                    win_h,
-                    wasabi::to_color(30, 20, 32),
-                );
-                wasabi::fill_rect(
-                    gop,
-                    win_x,
-                    win_y,
-                    win_w,
-                    title_h,
-                    wasabi::to_color(40, 30, 42),
-                );
-                wasabi::fill_rect(
-                    gop,
-                    win_x + 2,
-                    win_y + title_h,
-                    win_w - 4,
-                    win_h - title_h,
-                    wasabi::to_color(30, 20, 32),
-                );
-            });
-            let term_col0 = win_x / cell_w + 1;
-            let term_row0 = win_y / cell_h + 2;
-            let _ = st
-                .stdout()
-                .set_cursor_position(term_col0 as usize, term_row0 as usize);
-            kprintln!(st, "zam terminal");
-            last_px = None;
+            display.clear(desk_bg);
+            display.fill_rect(win_x, win_y, win_w, win_h, win_bg);
+            display.fill_rect(win_x, win_y, win_w, title_h, title_bg);
+            zam_text(
+                &mut display,
+                win_x + cell_w,
+                win_y,
+                "zam terminal",
+                text_fg,
+                title_bg,
+            );
+            let (x, y) = cell(win_x, win_y, 0, cur_row);
+            zam_text(&mut display, x, y, &line, text_fg, win_bg);
+            let (x, y) = cell(win_x, win_y, line.len(), cur_row);
+            zam_text(&mut display, x, y, "_", text_fg, win_bg);
             redraw_window = false;
         }
 
@@ -787,99 +3299,106 @@ fn zam_program(st: &mut SystemTable<Boot>) {
                             }
                         }
 
-                        if let Some((opx, opy)) = last_px {
-                            let obg = if opx >= win_x
-                                && opx < win_x + win_w
-                                && opy >= win_y
-                                && opy < win_y + win_h
-                            {
-                                if opy < win_y + title_h {
-                                    wasabi::to_color(40, 30, 42)
-                                } else if opx >= win_x + 2
-                                    && opx < win_x + win_w - 2
-                                    && opy >= win_y + title_h
-                                {
-                                    wasabi::to_color(30, 20, 32)
-                                } else {
-                                    wasabi::to_color(50, 40, 52)
-                                }
-                            } else {
-                                wasabi::to_color(48, 25, 52)
-                            };
-                            let _ = wasabi::with_gop(st.boot_services(), |gop| {
-                                wasabi::fill_rect(gop, opx, opy, 5, 5, obg);
-                            });
-                        }
-                        let _ = wasabi::with_gop(st.boot_services(), |gop| {
-                            wasabi::fill_rect(gop, px, py, 5, 5, wasabi::to_color(255, 255, 255));
-                        });
-                        last_px = Some((px, py));
+                        pointer_moved = true;
                         prev_left = left;
                     }
                 }
             }
         }
+        if pointer_moved || display.back().dirty().is_some() {
+            // Take the pointer off first so it neither gets painted over nor
+            // saves stale pixels, then put it back on the new frame.
+            let _ = wasabi::with_gop(st.boot_services(), |gop| {
+                cursor.hide(gop);
+                display.present(gop);
+                if pointer_handle.is_some() {
+                    cursor.move_to(gop, mouse_x as usize, mouse_y as usize);
+                }
+            });
+            pointer_moved = false;
+        }
 
-        let read_result = { st.stdin().read_key() };
-        match read_result {
-            Ok(Some(Key::Printable(c16))) => {
+        match keys.poll(st) {
+            Some(Key::Printable(c16)) => {
                 let c: char = c16.into();
                 match c {
-                    '\u{1b}' => break,
+                    '\u{1b}' | EOT => break,
                     '\r' | '\n' => {
-                        cur_row += 1;
-                        let max_rows = (win_h - title_h - 4) / cell_h;
-                        if cur_row >= max_rows {
-                            cur_row = max_rows.saturating_sub(1);
+                        let (x, y) = cell(win_x, win_y, line.len(), cur_row);
+                        zam_text(&mut display, x, y, " ", text_fg, win_bg);
+                        if cur_row + 1 < rows {
+                            cur_row += 1;
+                        } else {
+                            let (x, y) = cell(win_x, win_y, 0, cur_row);
+                            display.fill_rect(x, y, cols * cell_w, cell_h, win_bg);
                         }
-                        let _ = st
-                            .stdout()
-                            .set_cursor_position(win_x / cell_w + 1, win_y / cell_h + 2 + cur_row);
-                        kprintln!(st, "");
                         line.clear();
+                        let (x, y) = cell(win_x, win_y, 0, cur_row);
+                        zam_text(&mut display, x, y, "_", text_fg, win_bg);
                     }
                     '\u{8}' => {
-                        if !line.is_empty() {
-                            line.pop();
-                            let _ = st.stdout().set_cursor_position(
-                                win_x / cell_w + 1,
-                                win_y / cell_h + 2 + cur_row,
-                            );
-                            let mut s = heapless::String::<256>::new();
-                            let _ = s.push_str(line.as_str());
-                            let mut rem = ((win_w - 4) / cell_w).saturating_sub(s.len());
-                            while rem > 0 {
-                                let _ = s.push(' ');
-                                rem -= 1;
-                            }
-                            let _ = write!(st.stdout(), "{}", s.as_str());
-                            let _ = st.stdout().set_cursor_position(
-                                win_x / cell_w + 1 + line.len(),
-                                win_y / cell_h + 2 + cur_row,
-                            );
+                        if line.pop().is_some() {
+                            let (x, y) = cell(win_x, win_y, line.len(), cur_row);
+                            zam_text(&mut display, x, y, "_ ", text_fg, win_bg);
                         }
                     }
                     _ => {
-                        if line.len() + 1 >= ((win_w - 4) / cell_w) {
+                        if line.len() + 1 >= cols {
                             continue;
                         }
                         if line.push(c).is_ok() {
-                            let _ = write!(st.stdout(), "{}", c);
+                            let (x, y) = cell(win_x, win_y, line.len() - 1, cur_row);
+                            let mut typed = [0; 4];
+                            zam_text(
+                                &mut display,
+                                x,
+                                y,
+                                c.encode_utf8(&mut typed),
+                                text_fg,
+                                win_bg,
+                            );
+                            zam_text(&mut display, x + cell_w, y, "_", text_fg, win_bg);
                         }
                     }
                 }
             }
-            Ok(Some(Key::Special(sc))) => {
-                if sc == ScanCode::ESCAPE {
-                    break;
+            Some(Key::Special(sc)) => {
+                let step = (keys.step() * cell_w) as i32;
+                let (dx, dy) = match sc {
+                    ScanCode::ESCAPE => break,
+                    ScanCode::LEFT => (-step, 0),
+                    ScanCode::RIGHT => (step, 0),
+                    ScanCode::UP => (0, -step),
+                    ScanCode::DOWN => (0, step),
+                    _ => continue,
+                };
+                let nx = (win_x as i32 + dx).clamp(0, (screen_w - win_w) as i32) as usize;
+                let ny = (win_y as i32 + dy).clamp(0, (screen_h - win_h) as i32) as usize;
+                if nx != win_x || ny != win_y {
+                    win_x = nx;
+                    win_y = ny;
+                    redraw_window = true;
                 }
             }
-            Ok(None) => {
-                let _ = st.boot_services().stall(500);
-            }
-            Err(_) => {
-                let _ = st.boot_services().stall(1_000);
-            }
+            None => {}
         }
     }
+
+    let _ = console::stdout(st).clear();
+    let _ = console::stdout(st).enable_cursor(true);
+    ProgramResult::Exit
+}
+
+/// Draws `text` into zam's back buffer, one font cell per char.
+fn zam_text(display: &mut wasabi::Display, x: usize, y: usize, text: &str, fg: u32, bg: u32) {
+    for (i, c) in text.chars().enumerate() {
+        display.draw_char(
+            x + i * wasabi::font::DEFAULT.advance(),
+            y,
+            c,
+            &wasabi::font::DEFAULT,
+            fg,
+            bg,
+        );
+    }
 }
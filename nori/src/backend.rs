@@ -0,0 +1,220 @@
+use core::cell::RefCell;
+
+use uefi::proto::media::file::{
+    Directory, File, FileAttribute, FileHandle, FileInfo, FileMode, FileSystemInfo,
+    FileSystemVolumeLabel, FileType, RegularFile,
+};
+use uefi::proto::media::fs::SimpleFileSystem;
+use uefi::table::boot::{BootServices, ScopedProtocol};
+use uefi::{cstr16, CStr16, Status};
+
+use crate::{get_sfs, VolumeInfo};
+
+/// A directory entry or the result of a `stat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: heapless::String<256>,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// The filesystem operations nori's helpers are written against.
+///
+/// Paths use `/` or `\` as separators and are relative to the volume root.
+/// Methods take `&self` so a backend can be shared by recursive helpers
+/// (e.g. listing a directory while copying its children).
+pub trait FsBackend {
+    /// Describes the file or directory at `path`.
+    fn stat(&self, path: &str) -> uefi::Result<Entry>;
+
+    /// Calls `f` for every entry in the directory `path`, skipping `.` and `..`.
+    fn list(&self, path: &str, f: &mut dyn FnMut(&Entry)) -> uefi::Result;
+
+    /// Creates `path` as an empty regular file, truncating it if it exists.
+    /// A directory at `path` is left alone and reported as `ACCESS_DENIED`.
+    fn create(&self, path: &str) -> uefi::Result;
+
+    /// Reads into `buf` starting at `offset`, returning the bytes read (0 at EOF).
+    fn read(&self, path: &str, offset: u64, buf: &mut [u8]) -> uefi::Result<usize>;
+
+    /// Writes `data` at `offset` into the existing regular file `path`.
+    fn write(&self, path: &str, offset: u64, data: &[u8]) -> uefi::Result;
+
+    /// Deletes the file or empty directory at `path`.
+    fn remove(&self, path: &str) -> uefi::Result;
+
+    /// Creates the directory `path`. Succeeds if it already exists as a directory.
+    fn make_dir(&self, path: &str) -> uefi::Result;
+
+    /// Size, free space and label of the volume. Backends without one report
+    /// `UNSUPPORTED`.
+    fn volume(&self) -> uefi::Result<VolumeInfo> {
+        Err(Status::UNSUPPORTED.into())
+    }
+}
+
+/// [`FsBackend`] over the firmware's `SimpleFileSystem` protocol.
+///
+/// The protocol is opened exclusively once and kept for the lifetime of the
+/// backend; every operation opens its own handle from a fresh volume root.
+pub struct UefiFs<'a> {
+    sfs: RefCell<ScopedProtocol<'a, SimpleFileSystem>>,
+}
+
+impl<'a> UefiFs<'a> {
+    pub fn new(bt: &'a BootServices) -> uefi::Result<Self> {
+        Ok(Self {
+            sfs: RefCell::new(get_sfs(bt)?),
+        })
+    }
+
+    fn open(&self, path: &str, mode: FileMode, attr: FileAttribute) -> uefi::Result<FileHandle> {
+        let mut buf = [0u16; 260];
+        let path = encode_path(path, &mut buf)?;
+        let mut root = self.sfs.borrow_mut().open_volume()?;
+        root.open(path, mode, attr)
+    }
+
+    fn open_regular(&self, path: &str, mode: FileMode) -> uefi::Result<RegularFile> {
+        self.open(path, mode, FileAttribute::empty())?
+            .into_regular_file()
+            .ok_or(Status::INVALID_PARAMETER.into())
+    }
+}
+
+impl FsBackend for UefiFs<'_> {
+    fn stat(&self, path: &str) -> uefi::Result<Entry> {
+        let mut file = self.open(path, FileMode::Read, FileAttribute::empty())?;
+        let mut buf = [0u8; 1024];
+        let info = file
+            .get_info::<FileInfo>(&mut buf)
+            .map_err(|e| e.to_err_without_payload())?;
+        Ok(entry_from_info(info))
+    }
+
+    fn list(&self, path: &str, f: &mut dyn FnMut(&Entry)) -> uefi::Result {
+        let mut dir = self
+            .open(path, FileMode::Read, FileAttribute::empty())?
+            .into_directory()
+            .ok_or(Status::INVALID_PARAMETER)?;
+
+        let mut buf = [0u8; 1024];
+        while let Some(info) = dir
+            .read_entry(&mut buf)
+            .map_err(|e| e.to_err_without_payload())?
+        {
+            let name = info.file_name();
+            if name == cstr16!(".") || name == cstr16!("..") {
+                continue;
+            }
+            f(&entry_from_info(info));
+        }
+        Ok(())
+    }
+
+    fn create(&self, path: &str) -> uefi::Result {
+        if let Ok(handle) = self.open(path, FileMode::ReadWrite, FileAttribute::empty()) {
+            match handle.into_type()? {
+                FileType::Dir(_) => return Err(Status::ACCESS_DENIED.into()),
+                FileType::Regular(file) => file.delete()?,
+            }
+        }
+        let mut file = self.open_regular(path, FileMode::CreateReadWrite)?;
+        file.flush()
+    }
+
+    fn read(&self, path: &str, offset: u64, buf: &mut [u8]) -> uefi::Result<usize> {
+        let mut file = self.open_regular(path, FileMode::Read)?;
+        file.set_position(offset)?;
+        file.read(buf).map_err(|e| e.to_err_without_payload())
+    }
+
+    fn write(&self, path: &str, offset: u64, data: &[u8]) -> uefi::Result {
+        let mut file = self.open_regular(path, FileMode::ReadWrite)?;
+        file.set_position(offset)?;
+        file.write(data).map_err(|e| e.to_err_without_payload())?;
+        file.flush()
+    }
+
+    fn remove(&self, path: &str) -> uefi::Result {
+        self.open(path, FileMode::ReadWrite, FileAttribute::empty())?
+            .delete()
+    }
+
+    fn make_dir(&self, path: &str) -> uefi::Result {
+        let mut buf = [0u16; 260];
+        let path = encode_path(path, &mut buf)?;
+        let mut root = self.sfs.borrow_mut().open_volume()?;
+        create_dir(&mut root, path)
+    }
+
+    /// The label comes from `FileSystemInfo`, falling back to
+    /// `FileSystemVolumeLabel`; if neither has one it is left empty.
+    fn volume(&self) -> uefi::Result<VolumeInfo> {
+        let mut root = self.sfs.borrow_mut().open_volume()?;
+        let mut buf = [0u8; 1024];
+        let info = root
+            .get_info::<FileSystemInfo>(&mut buf)
+            .map_err(|e| e.to_err_without_payload())?;
+        let mut volume = VolumeInfo {
+            label: heapless::String::new(),
+            total_bytes: info.volume_size(),
+            free_bytes: info.free_space(),
+        };
+        push_lossy(&mut volume.label, info.volume_label());
+
+        if volume.label.is_empty() {
+            if let Ok(label) = root.get_info::<FileSystemVolumeLabel>(&mut buf) {
+                push_lossy(&mut volume.label, label.volume_label());
+            }
+        }
+        Ok(volume)
+    }
+}
+
+fn push_lossy<const N: usize>(out: &mut heapless::String<N>, s: &CStr16) {
+    for ch in s.iter() {
+        if out.push(char::from(*ch)).is_err() {
+            break;
+        }
+    }
+}
+
+pub(crate) fn entry_from_info(info: &FileInfo) -> Entry {
+    let mut name = heapless::String::new();
+    for ch in info.file_name().iter() {
+        if name.push(char::from(*ch)).is_err() {
+            break;
+        }
+    }
+    Entry {
+        name,
+        is_dir: info.is_directory(),
+        size: info.file_size(),
+    }
+}
+
+fn create_dir(root: &mut Directory, path: &CStr16) -> uefi::Result {
+    root.open(path, FileMode::CreateReadWrite, FileAttribute::DIRECTORY)?
+        .into_directory()
+        .map(|_| ())
+        .ok_or(Status::INVALID_PARAMETER.into())
+}
+
+/// Encodes `path` as a UCS-2 `\`-separated path. An empty path names the root.
+pub(crate) fn encode_path<'a>(path: &str, buf: &'a mut [u16]) -> uefi::Result<&'a CStr16> {
+    let path = if path.is_empty() { "/" } else { path };
+    let mut len = 0;
+    for ch in path.chars() {
+        let ch = if ch == '/' { '\\' } else { ch };
+        let mut units = [0u16; 2];
+        let encoded = ch.encode_utf16(&mut units);
+        if encoded.len() != 1 || len + 1 >= buf.len() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        buf[len] = encoded[0];
+        len += 1;
+    }
+    buf[len] = 0;
+    CStr16::from_u16_with_nul(&buf[..=len]).map_err(|_| Status::INVALID_PARAMETER.into())
+}
@@ -1,11 +1,51 @@
 #![no_std]
 
+extern crate alloc;
+
+mod backend;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+
+pub use backend::{Entry, FsBackend, UefiFs};
+#[cfg(any(test, feature = "mock"))]
+pub use mock::MockFs;
+
+use alloc::vec::Vec;
 use log::info;
 use uefi::prelude::*;
+use uefi::proto::media::file::{Directory, File, FileAttribute, FileInfo, FileMode};
 use uefi::proto::media::fs::SimpleFileSystem;
 use uefi::table::boot::{BootServices, ScopedProtocol, SearchType};
 use uefi::{cstr16, Identify};
-use uefi::CStr16;
+use uefi::{CStr16, Status};
+
+/// Why a whole-file helper like [`read_file`] failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoriError {
+    /// No `SimpleFileSystem` protocol could be opened.
+    NoFs,
+    /// The path, or one of its parent directories, does not exist.
+    NotFound,
+    /// The path names a directory where a regular file was expected.
+    IsDirectory,
+    /// The firmware reported an error while reading.
+    Read,
+    /// The firmware reported an error while creating or writing.
+    Write,
+    /// Something already exists at the path being created.
+    AlreadyExists,
+    /// The directory being deleted still has entries.
+    DirectoryNotEmpty,
+}
+
+/// Capacity and label of the boot volume, as returned by [`volume_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeInfo {
+    /// Empty when the filesystem has no label.
+    pub label: heapless::String<64>,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+}
 
 pub fn list_root_directory(system_table: &mut SystemTable<Boot>) {
     let bt = system_table.boot_services();
@@ -36,29 +76,680 @@ pub fn get_sfs<'a>(bt: &'a BootServices) -> uefi::Result<ScopedProtocol<'a, Simp
     bt.open_protocol_exclusive::<SimpleFileSystem>(handle)
 }
 
-pub fn list_root<'a, F>(system_table: &mut SystemTable<Boot>, mut f: F)
+pub fn list_root<'a, F>(system_table: &mut SystemTable<Boot>, mut f: F) -> Result<(), NoriError>
 where
     F: FnMut(&CStr16),
 {
-    let bt = system_table.boot_services();
-    let mut sfs = get_sfs(bt).expect("Failed to get SimpleFileSystem protocol");
-    let mut root = sfs.open_volume().expect("Failed to open volume");
+    list_root_info(system_table, |info| f(info.file_name()))
+}
 
-    let mut buffer = [0u8; 1024];
-    loop {
-        let info = match root
-            .read_entry(&mut buffer)
-            .expect("Failed to read directory entry")
-        {
-            Some(info) => info,
-            None => break,
-        };
+/// Calls `f` with the name, size and kind of every entry in the root.
+///
+/// Entries are read into a 1024-byte buffer, which holds a `FileInfo` with
+/// the longest FAT name (255 UCS-2 characters); [`Entry::name`] keeps up to
+/// 256 bytes and drops anything past that.
+pub fn list_root_entries<F>(system_table: &mut SystemTable<Boot>, mut f: F) -> Result<(), NoriError>
+where
+    F: FnMut(&Entry),
+{
+    list_root_info(system_table, |info| f(&backend::entry_from_info(info)))
+}
+
+/// Calls `f` with the `FileInfo` of every entry in the root.
+pub fn list_root_info<F>(system_table: &mut SystemTable<Boot>, f: F) -> Result<(), NoriError>
+where
+    F: FnMut(&FileInfo),
+{
+    list_dir_info(system_table, "", f)
+}
 
+/// Calls `f` with the `FileInfo` of every entry in the directory `path`.
+pub fn list_dir_info<F>(
+    system_table: &mut SystemTable<Boot>,
+    path: &str,
+    mut f: F,
+) -> Result<(), NoriError>
+where
+    F: FnMut(&FileInfo),
+{
+    let mut sfs = get_sfs(system_table.boot_services()).map_err(|_| NoriError::NoFs)?;
+    let mut dir = open_dir(&mut sfs, path).map_err(|e| lookup_error(e, NoriError::Read))?;
+
+    let mut buf = [0u8; 1024];
+    while let Some(info) = dir.read_entry(&mut buf).map_err(|_| NoriError::Read)? {
         let name = info.file_name();
         if name == cstr16!(".") || name == cstr16!("..") {
             continue;
         }
+        f(info);
+    }
+    Ok(())
+}
+
+/// Calls `f` for every entry of the directory `dir` whose name matches the
+/// `*`/`?` glob `pattern` (see [`glob_match`]).
+pub fn glob<F>(fs: &dyn FsBackend, dir: &str, pattern: &str, mut f: F) -> Result<(), NoriError>
+where
+    F: FnMut(&Entry),
+{
+    fs.list(dir, &mut |entry| {
+        if glob_match(pattern, &entry.name) {
+            f(entry);
+        }
+    })
+    .map_err(|e| lookup_error(e, NoriError::Read))
+}
+
+/// Whether `name` matches `pattern`, ignoring ASCII case as FAT does.
+///
+/// `*` matches any run of characters (including none) and `?` exactly one;
+/// everything else must match literally, so a pattern without wildcards is
+/// an exact comparison.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let (mut p, mut n) = (pattern, name);
+    // Pattern just past the last `*`, and where in `name` that star's match ends.
+    let mut star: Option<(&str, &str)> = None;
+    while let Some(nc) = n.chars().next() {
+        let mut pc = p.chars();
+        match pc.next() {
+            Some('*') => {
+                p = pc.as_str();
+                star = Some((p, n));
+            }
+            Some(c) if c == '?' || c.eq_ignore_ascii_case(&nc) => {
+                p = pc.as_str();
+                n = &n[nc.len_utf8()..];
+            }
+            _ => {
+                let Some((sp, sn)) = star else {
+                    return false;
+                };
+                let mut sc = sn.chars();
+                sc.next();
+                p = sp;
+                n = sc.as_str();
+                star = Some((sp, n));
+            }
+        }
+    }
+    p.chars().all(|c| c == '*')
+}
+
+/// Calls `f` with the name and depth (0 for direct children) of every entry
+/// below the directory `dir`, each directory before its contents. An empty
+/// `dir` is the root. Directories at `max_depth` are reported but not
+/// entered, so a looping or corrupt filesystem can't exhaust the stack.
+pub fn walk<F>(fs: &dyn FsBackend, dir: &str, max_depth: usize, mut f: F) -> uefi::Result
+where
+    F: FnMut(&str, usize),
+{
+    walk_dir(fs, dir, 0, max_depth, &mut f)
+}
+
+fn walk_dir(
+    fs: &dyn FsBackend,
+    dir: &str,
+    depth: usize,
+    max_depth: usize,
+    f: &mut dyn FnMut(&str, usize),
+) -> uefi::Result {
+    let mut result = Ok(());
+    fs.list(dir, &mut |entry| {
+        if result.is_err() {
+            return;
+        }
+        f(&entry.name, depth);
+        if entry.is_dir && depth < max_depth {
+            result =
+                join(dir, &entry.name).and_then(|sub| walk_dir(fs, &sub, depth + 1, max_depth, f));
+        }
+    })?;
+    result
+}
+
+/// Reports the size, free space and label of the volume.
+pub fn volume_info(fs: &dyn FsBackend) -> Result<VolumeInfo, NoriError> {
+    fs.volume().map_err(|_| NoriError::Read)
+}
+
+/// Reads the regular file at `path` into memory.
+pub fn read_file(fs: &dyn FsBackend, path: &str) -> Result<Vec<u8>, NoriError> {
+    if fs
+        .stat(path)
+        .map_err(|e| lookup_error(e, NoriError::Read))?
+        .is_dir
+    {
+        return Err(NoriError::IsDirectory);
+    }
+
+    let mut buf = [0u8; 4096];
+    let mut data = Vec::new();
+    loop {
+        let read = fs
+            .read(path, data.len() as u64, &mut buf)
+            .map_err(|_| NoriError::Read)?;
+        if read == 0 {
+            return Ok(data);
+        }
+        data.extend_from_slice(&buf[..read]);
+    }
+}
+
+/// Writes `data` to the file at `path`, creating it if needed.
+///
+/// With `append` the bytes go after the existing contents, otherwise they
+/// replace them. The parent directory must already exist: a missing one is
+/// reported as [`NoriError::NotFound`] rather than created.
+pub fn save_file(
+    fs: &dyn FsBackend,
+    path: &str,
+    data: &[u8],
+    append: bool,
+) -> Result<(), NoriError> {
+    let offset = match fs.stat(path) {
+        Ok(entry) if entry.is_dir => return Err(NoriError::IsDirectory),
+        Ok(entry) if append => entry.size,
+        _ => {
+            fs.create(path)
+                .map_err(|e| lookup_error(e, NoriError::Write))?;
+            0
+        }
+    };
+    fs.write(path, offset, data).map_err(|_| NoriError::Write)
+}
+
+/// Creates the directory `path` inside its existing parent.
+///
+/// Only the last component is created; a missing parent is
+/// [`NoriError::NotFound`], and an existing file or directory at `path` is
+/// [`NoriError::AlreadyExists`].
+pub fn create_dir(fs: &dyn FsBackend, path: &str) -> Result<(), NoriError> {
+    if split_parent(path).1.is_empty() || fs.stat(path).is_ok() {
+        return Err(NoriError::AlreadyExists);
+    }
+    fs.make_dir(path)
+        .map_err(|e| lookup_error(e, NoriError::Write))
+}
+
+/// Deletes the file or empty directory at `path`.
+pub fn delete(fs: &dyn FsBackend, path: &str) -> Result<(), NoriError> {
+    let entry = fs
+        .stat(path)
+        .map_err(|e| lookup_error(e, NoriError::Write))?;
+    if entry.is_dir {
+        let mut empty = true;
+        fs.list(path, &mut |_| empty = false)
+            .map_err(|_| NoriError::Read)?;
+        if !empty {
+            return Err(NoriError::DirectoryNotEmpty);
+        }
+    }
+    fs.remove(path).map_err(|_| NoriError::Write)
+}
+
+/// Moves the regular file `from` to `to`, which must not exist yet.
+///
+/// FAT has no rename here, so this copies the file and then deletes `from`;
+/// a failure after the copy can leave both in place. Directories are
+/// rejected with [`NoriError::IsDirectory`].
+pub fn rename(fs: &dyn FsBackend, from: &str, to: &str) -> Result<(), NoriError> {
+    let entry = fs
+        .stat(from)
+        .map_err(|e| lookup_error(e, NoriError::Read))?;
+    if entry.is_dir {
+        return Err(NoriError::IsDirectory);
+    }
+    if exists(fs, to) {
+        return Err(NoriError::AlreadyExists);
+    }
+    copy_file(fs, from, to).map_err(|e| lookup_error(e, NoriError::Write))?;
+    fs.remove(from).map_err(|_| NoriError::Write)
+}
+
+/// Opens the boot volume as a [`UefiFs`] for the length of `f`, so callers
+/// holding the system table don't keep the protocol open.
+pub fn with_boot_fs<T, F>(bt: &BootServices, f: F) -> Result<T, NoriError>
+where
+    F: FnOnce(&dyn FsBackend) -> Result<T, NoriError>,
+{
+    let fs = UefiFs::new(bt).map_err(|_| NoriError::NoFs)?;
+    f(&fs)
+}
+
+/// Opens the directory `path`; an empty path (or just separators) is the root.
+fn open_dir(sfs: &mut SimpleFileSystem, path: &str) -> uefi::Result<Directory> {
+    let mut root = sfs.open_volume()?;
+    if path.split(['/', '\\']).all(str::is_empty) {
+        return Ok(root);
+    }
+    let mut buf = [0u16; 260];
+    let path = backend::encode_path(path, &mut buf)?;
+    root.open(path, FileMode::Read, FileAttribute::empty())?
+        .into_directory()
+        .ok_or(Status::INVALID_PARAMETER.into())
+}
+
+/// Splits `path` into its parent directory and final component.
+fn split_parent(path: &str) -> (&str, &str) {
+    let path = path.trim_end_matches(['/', '\\']);
+    path.rsplit_once(['/', '\\']).unwrap_or(("", path))
+}
+
+/// Maps a failed lookup, treating a missing component or a file
+/// where a directory was expected as [`NoriError::NotFound`].
+fn lookup_error(err: uefi::Error, other: NoriError) -> NoriError {
+    match err.status() {
+        Status::NOT_FOUND | Status::INVALID_PARAMETER => NoriError::NotFound,
+        _ => other,
+    }
+}
+
+/// Writes `data` to `<path>.tmp`, flushes it, then moves it over `path`.
+///
+/// FAT has no rename, so the move is a copy followed by deleting the temp file.
+/// That makes this "atomic-ish" only: the target is never left half-written by
+/// the data write itself, but a crash during the copy can still truncate it.
+/// The temp file is removed whether or not the write succeeds, and a
+/// directory at `path` is refused with `INVALID_PARAMETER`.
+pub fn write_file_atomic(fs: &dyn FsBackend, path: &str, data: &[u8]) -> uefi::Result {
+    if fs.stat(path).is_ok_and(|entry| entry.is_dir) {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let mut tmp = heapless::String::<260>::new();
+    tmp.push_str(path)
+        .and_then(|_| tmp.push_str(".tmp"))
+        .map_err(|_| Status::INVALID_PARAMETER)?;
+
+    let _ = fs.remove(&tmp);
+    let result = fs
+        .create(&tmp)
+        .and_then(|_| fs.write(&tmp, 0, data))
+        .and_then(|_| copy_file(fs, &tmp, path));
+    let _ = fs.remove(&tmp);
+    result
+}
+
+/// Returns `true` if `path` exists on the filesystem, ignoring ASCII case.
+pub fn exists(fs: &dyn FsBackend, path: &str) -> bool {
+    open_path(fs, path).is_ok()
+}
+
+/// Looks up `path`, returning it as spelled on disk together with its entry.
+///
+/// An exact lookup is tried first. If that reports `NOT_FOUND`, each component
+/// is matched against its parent's listing ignoring ASCII case, so
+/// `readme.txt` finds `README.TXT`.
+pub fn open_path(fs: &dyn FsBackend, path: &str) -> uefi::Result<(heapless::String<260>, Entry)> {
+    let too_long = |_| uefi::Error::from(Status::INVALID_PARAMETER);
+    match fs.stat(path) {
+        Ok(entry) => {
+            let mut exact = heapless::String::new();
+            exact.push_str(path).map_err(too_long)?;
+            return Ok((exact, entry));
+        }
+        Err(e) if e.status() != Status::NOT_FOUND => return Err(e),
+        Err(_) => {}
+    }
+
+    let mut real = heapless::String::<260>::new();
+    let mut last = None;
+    for part in path.split(['/', '\\']).filter(|p| !p.is_empty()) {
+        let mut found = None;
+        fs.list(&real, &mut |entry| {
+            if found.is_none() && entry.name.eq_ignore_ascii_case(part) {
+                found = Some(entry.clone());
+            }
+        })?;
+        let entry = found.ok_or(Status::NOT_FOUND)?;
+        if !real.is_empty() {
+            real.push('/').map_err(too_long)?;
+        }
+        real.push_str(&entry.name).map_err(too_long)?;
+        last = Some(entry);
+    }
+    last.map(|entry| (real, entry))
+        .ok_or(Status::NOT_FOUND.into())
+}
+
+/// Copies the regular file `from` to `to`, replacing `to` if it exists.
+///
+/// A directory `to` receives the file under its own name. Copying a file
+/// onto itself (`a` to `./A`, say) is rejected with `INVALID_PARAMETER`
+/// before `to` is truncated, as is a directory in the way of the copy.
+pub fn copy_file(fs: &dyn FsBackend, from: &str, to: &str) -> uefi::Result {
+    if fs.stat(from)?.is_dir {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let inside;
+    let to = if fs.stat(to).is_ok_and(|entry| entry.is_dir) {
+        inside = join(to, split_parent(from).1)?;
+        inside.as_str()
+    } else {
+        to
+    };
+    if same_path(from, to) || fs.stat(to).is_ok_and(|entry| entry.is_dir) {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    fs.create(to)?;
+
+    let mut buf = [0u8; 4096];
+    let mut offset = 0;
+    loop {
+        let read = fs.read(from, offset, &mut buf)?;
+        if read == 0 {
+            return Ok(());
+        }
+        fs.write(to, offset, &buf[..read])?;
+        offset += read as u64;
+    }
+}
+
+/// Deepest directory nesting `copy_dir` will descend into.
+pub const MAX_COPY_DEPTH: usize = 16;
+
+/// Recursively copies the directory `from` to `to`.
+///
+/// Existing files under `to` are overwritten and existing directories are
+/// merged into; callers that want "destination must not exist" semantics
+/// should check with [`exists`] first. Copying a directory into itself
+/// (`to` equal to or below `from`) is rejected with `INVALID_PARAMETER`.
+pub fn copy_dir(fs: &dyn FsBackend, from: &str, to: &str) -> uefi::Result {
+    if is_within(from, to) {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    copy_tree(fs, from, to, 0)
+}
+
+/// Whether `path` names `dir` itself or something below it.
+pub fn is_within(dir: &str, path: &str) -> bool {
+    let is_sep = |c: char| c == '/' || c == '\\';
+    let dir = dir.trim_matches(is_sep);
+    let path = path.trim_matches(is_sep);
+    if dir.is_empty() {
+        return true;
+    }
+    let (d, p) = (dir.as_bytes(), path.as_bytes());
+    let same = |a: u8, b: u8| {
+        (a == b'/' || a == b'\\') && (b == b'/' || b == b'\\') || a.eq_ignore_ascii_case(&b)
+    };
+    p.len() >= d.len()
+        && d.iter().zip(p).all(|(&a, &b)| same(a, b))
+        && (p.len() == d.len() || p[d.len()] == b'/' || p[d.len()] == b'\\')
+}
+
+/// Whether `a` and `b` name the same node once `.` and `..` are resolved,
+/// ignoring ASCII case as FAT does.
+fn same_path(a: &str, b: &str) -> bool {
+    fn parts(path: &str) -> Option<heapless::Vec<&str, 64>> {
+        let mut out = heapless::Vec::new();
+        for part in path.split(['/', '\\']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    out.pop();
+                }
+                _ => out.push(part).ok()?,
+            }
+        }
+        Some(out)
+    }
+    match (parts(a), parts(b)) {
+        (Some(a), Some(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(x, y)| x.eq_ignore_ascii_case(y))
+        }
+        _ => false,
+    }
+}
+
+fn copy_tree(fs: &dyn FsBackend, from: &str, to: &str, depth: usize) -> uefi::Result {
+    if depth >= MAX_COPY_DEPTH {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    fs.make_dir(to)?;
+
+    let mut result = Ok(());
+    fs.list(from, &mut |entry| {
+        if result.is_err() {
+            return;
+        }
+        result = join(from, &entry.name).and_then(|src| {
+            let dst = join(to, &entry.name)?;
+            if entry.is_dir {
+                copy_tree(fs, &src, &dst, depth + 1)
+            } else {
+                copy_file(fs, &src, &dst)
+            }
+        });
+    })?;
+    result
+}
+
+fn join(dir: &str, name: &str) -> uefi::Result<heapless::String<260>> {
+    let mut out = heapless::String::new();
+    out.push_str(dir.trim_end_matches(['/', '\\']))
+        .and_then(|_| out.push('/'))
+        .and_then(|_| out.push_str(name))
+        .map_err(|_| Status::BUFFER_TOO_SMALL)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn sample() -> MockFs {
+        let fs = MockFs::new();
+        fs.make_dir("EFI").unwrap();
+        fs.make_dir("EFI/BOOT").unwrap();
+        fs.insert_file("EFI/BOOT/BOOTX64.EFI", b"boot").unwrap();
+        fs.insert_file("README.TXT", b"hello").unwrap();
+        fs
+    }
+
+    #[test]
+    fn open_path_prefers_exact_match() {
+        let fs = sample();
+        let (real, entry) = open_path(&fs, "README.TXT").unwrap();
+        assert_eq!(real, "README.TXT");
+        assert_eq!(entry.size, 5);
+    }
+
+    #[test]
+    fn open_path_falls_back_to_case_insensitive_lookup() {
+        let fs = sample();
+        let (real, entry) = open_path(&fs, "/efi/boot/bootx64.efi").unwrap();
+        assert_eq!(real, "EFI/BOOT/BOOTX64.EFI");
+        assert!(!entry.is_dir);
+
+        let (real, entry) = open_path(&fs, "efi\\Boot").unwrap();
+        assert_eq!(real, "EFI/BOOT");
+        assert!(entry.is_dir);
+
+        assert!(exists(&fs, "readme.txt"));
+        assert!(!exists(&fs, "readme.md"));
+        assert_eq!(
+            open_path(&fs, "efi/missing").unwrap_err().status(),
+            Status::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn copy_dir_refuses_to_copy_into_itself() {
+        let fs = sample();
+        for to in ["EFI", "EFI/BOOT/sub", "/efi/boot/", "\\EFI\\x"] {
+            assert_eq!(
+                copy_dir(&fs, "EFI", to).unwrap_err().status(),
+                Status::INVALID_PARAMETER,
+                "{to}"
+            );
+        }
+        assert!(!exists(&fs, "EFI/BOOT/sub"));
+    }
+
+    #[test]
+    fn copy_dir_copies_the_whole_tree() {
+        let fs = sample();
+        copy_dir(&fs, "EFI", "EFI2").unwrap();
+        assert_eq!(fs.contents("EFI2/BOOT/BOOTX64.EFI").unwrap(), b"boot");
+        // A sibling whose name merely starts with `from` is not inside it.
+        assert!(!is_within("EFI", "EFI2"));
+    }
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("*.txt", "notes.txt"));
+        assert!(glob_match("*.TXT", "notes.txt"));
+        assert!(!glob_match("*.txt", "notes.txt.bak"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(glob_match("a*b", "abab"));
+        assert!(glob_match("**x", "x"));
+        assert!(glob_match("?", "a"));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("?", "ab"));
+        assert!(glob_match("r?adme*", "README.TXT"));
+    }
+
+    #[test]
+    fn glob_match_without_wildcards_is_exact() {
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+        assert!(!glob_match("a", ""));
+        assert!(glob_match("Boot", "BOOT"));
+        assert!(!glob_match("boot", "bootx64"));
+    }
+
+    #[test]
+    fn glob_filters_a_listing() {
+        let fs = sample();
+        let mut names = Vec::new();
+        glob(&fs, "/", "*.txt", |entry| {
+            names.push(String::from(entry.name.as_str()))
+        })
+        .unwrap();
+        assert_eq!(names, ["README.TXT"]);
+        assert_eq!(
+            glob(&fs, "nope", "*", |_| {}).unwrap_err(),
+            NoriError::NotFound
+        );
+    }
+
+    #[test]
+    fn read_file_returns_contents_past_one_buffer() {
+        let fs = sample();
+        let big: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        fs.insert_file("big.bin", &big).unwrap();
+        assert_eq!(read_file(&fs, "big.bin").unwrap(), big);
+        assert_eq!(read_file(&fs, "EFI").unwrap_err(), NoriError::IsDirectory);
+        assert_eq!(read_file(&fs, "nope").unwrap_err(), NoriError::NotFound);
+    }
+
+    #[test]
+    fn copy_file_replaces_the_target() {
+        let fs = sample();
+        fs.insert_file("old.txt", b"a much longer old body")
+            .unwrap();
+        copy_file(&fs, "README.TXT", "old.txt").unwrap();
+        assert_eq!(fs.contents("old.txt").unwrap(), b"hello");
+        assert_eq!(
+            copy_file(&fs, "EFI", "x").unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+    }
+
+    #[test]
+    fn create_refuses_to_replace_a_directory() {
+        let fs = sample();
+        fs.make_dir("empty").unwrap();
+        let backend: &dyn FsBackend = &fs;
+        for dir in ["empty", "EFI/BOOT"] {
+            assert_eq!(
+                backend.create(dir).unwrap_err().status(),
+                Status::ACCESS_DENIED,
+                "{dir}"
+            );
+            assert!(backend.stat(dir).unwrap().is_dir);
+        }
+        assert!(exists(&fs, "EFI/BOOT/BOOTX64.EFI"));
+    }
+
+    #[test]
+    fn copy_file_onto_itself_keeps_the_source() {
+        let fs = sample();
+        for to in [
+            "README.TXT",
+            "./README.TXT",
+            "readme.txt",
+            "EFI/../README.TXT",
+        ] {
+            assert_eq!(
+                copy_file(&fs, "README.TXT", to).unwrap_err().status(),
+                Status::INVALID_PARAMETER,
+                "{to}"
+            );
+            assert_eq!(fs.contents("README.TXT").unwrap(), b"hello");
+        }
+    }
+
+    #[test]
+    fn copy_file_into_a_directory_keeps_the_name() {
+        let fs = sample();
+        copy_file(&fs, "README.TXT", "EFI").unwrap();
+        assert_eq!(fs.contents("EFI/README.TXT").unwrap(), b"hello");
+        assert!(fs.stat("EFI").unwrap().is_dir);
+
+        // Its own directory resolves back to the source.
+        assert_eq!(
+            copy_file(&fs, "EFI/BOOT/BOOTX64.EFI", "EFI/BOOT")
+                .unwrap_err()
+                .status(),
+            Status::INVALID_PARAMETER
+        );
+        assert_eq!(fs.contents("EFI/BOOT/BOOTX64.EFI").unwrap(), b"boot");
+    }
+
+    #[test]
+    fn write_file_atomic_replaces_and_cleans_up() {
+        let fs = sample();
+        write_file_atomic(&fs, "README.TXT", b"new").unwrap();
+        assert_eq!(fs.contents("README.TXT").unwrap(), b"new");
+        assert!(!exists(&fs, "README.TXT.tmp"));
+
+        assert!(write_file_atomic(&fs, "nope/a.txt", b"x").is_err());
+        assert!(!exists(&fs, "nope/a.txt.tmp"));
+        assert!(write_file_atomic(&fs, "EFI/BOOT", b"x").is_err());
+        assert!(fs.stat("EFI/BOOT").unwrap().is_dir);
+        assert!(!exists(&fs, "EFI/BOOT.tmp"));
+    }
+
+    #[test]
+    fn walk_visits_parents_first_and_stops_at_max_depth() {
+        let fs = sample();
+        let mut seen = Vec::new();
+        walk(&fs, "", 1, |name, depth| {
+            seen.push((String::from(name), depth))
+        })
+        .unwrap();
+        let seen: Vec<(&str, usize)> = seen.iter().map(|(n, d)| (n.as_str(), *d)).collect();
+        assert_eq!(seen, [("EFI", 0), ("BOOT", 1), ("README.TXT", 0)]);
+    }
 
-        f(name);
+    #[test]
+    fn walk_starts_at_the_given_directory() {
+        let fs = sample();
+        fs.make_dir("EFI/BOOT/FONTS").unwrap();
+        let mut seen = Vec::new();
+        walk(&fs, "/EFI", 4, |name, depth| {
+            seen.push((String::from(name), depth))
+        })
+        .unwrap();
+        let seen: Vec<(&str, usize)> = seen.iter().map(|(n, d)| (n.as_str(), *d)).collect();
+        assert_eq!(seen, [("BOOT", 0), ("BOOTX64.EFI", 1), ("FONTS", 1)]);
+        assert!(walk(&fs, "README.TXT", 4, |_, _| {}).is_err());
     }
 }
@@ -0,0 +1,198 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use uefi::Status;
+
+use crate::backend::{Entry, FsBackend};
+
+enum Node {
+    Dir,
+    File(Vec<u8>),
+}
+
+/// In-memory [`FsBackend`] for exercising nori's helpers off-firmware.
+///
+/// Paths are normalised to `/`-separated keys without leading or trailing
+/// separators; the root (`""`) always exists. Names are case-sensitive,
+/// unlike FAT.
+#[derive(Default)]
+pub struct MockFs {
+    nodes: RefCell<BTreeMap<String, Node>>,
+}
+
+impl MockFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates or replaces the file `path` with `data`. Parents must exist.
+    pub fn insert_file(&self, path: &str, data: &[u8]) -> uefi::Result {
+        self.create(path)?;
+        self.write(path, 0, data)
+    }
+
+    /// Returns a copy of the contents of the file `path`.
+    pub fn contents(&self, path: &str) -> Option<Vec<u8>> {
+        match self.nodes.borrow().get(&normalize(path)) {
+            Some(Node::File(data)) => Some(data.clone()),
+            _ => None,
+        }
+    }
+
+    fn is_dir(&self, key: &str) -> bool {
+        key.is_empty() || matches!(self.nodes.borrow().get(key), Some(Node::Dir))
+    }
+
+    fn check_parent(&self, key: &str) -> uefi::Result {
+        let parent = key.rsplit_once('/').map_or("", |(parent, _)| parent);
+        if self.is_dir(parent) {
+            Ok(())
+        } else {
+            Err(Status::NOT_FOUND.into())
+        }
+    }
+}
+
+impl FsBackend for MockFs {
+    fn stat(&self, path: &str) -> uefi::Result<Entry> {
+        let key = normalize(path);
+        let name = key.rsplit('/').next().unwrap_or("");
+        let (is_dir, size) = if key.is_empty() {
+            (true, 0)
+        } else {
+            match self.nodes.borrow().get(&key) {
+                Some(Node::Dir) => (true, 0),
+                Some(Node::File(data)) => (false, data.len() as u64),
+                None => return Err(Status::NOT_FOUND.into()),
+            }
+        };
+        Ok(entry(name, is_dir, size))
+    }
+
+    fn list(&self, path: &str, f: &mut dyn FnMut(&Entry)) -> uefi::Result {
+        let key = normalize(path);
+        if !self.is_dir(&key) {
+            return Err(Status::NOT_FOUND.into());
+        }
+
+        // Collect first so `f` may modify the filesystem.
+        let entries: Vec<Entry> = self
+            .nodes
+            .borrow()
+            .iter()
+            .filter_map(|(k, node)| {
+                let rest = if key.is_empty() {
+                    k.as_str()
+                } else {
+                    k.strip_prefix(key.as_str())?.strip_prefix('/')?
+                };
+                if rest.contains('/') {
+                    return None;
+                }
+                Some(match node {
+                    Node::Dir => entry(rest, true, 0),
+                    Node::File(data) => entry(rest, false, data.len() as u64),
+                })
+            })
+            .collect();
+        for e in &entries {
+            f(e);
+        }
+        Ok(())
+    }
+
+    fn create(&self, path: &str) -> uefi::Result {
+        let key = normalize(path);
+        if self.is_dir(&key) {
+            return Err(Status::ACCESS_DENIED.into());
+        }
+        self.check_parent(&key)?;
+        self.nodes.borrow_mut().insert(key, Node::File(Vec::new()));
+        Ok(())
+    }
+
+    fn read(&self, path: &str, offset: u64, buf: &mut [u8]) -> uefi::Result<usize> {
+        match self.nodes.borrow().get(&normalize(path)) {
+            Some(Node::File(data)) => {
+                let start = (offset as usize).min(data.len());
+                let n = buf.len().min(data.len() - start);
+                buf[..n].copy_from_slice(&data[start..start + n]);
+                Ok(n)
+            }
+            Some(Node::Dir) => Err(Status::INVALID_PARAMETER.into()),
+            None => Err(Status::NOT_FOUND.into()),
+        }
+    }
+
+    fn write(&self, path: &str, offset: u64, data: &[u8]) -> uefi::Result {
+        match self.nodes.borrow_mut().get_mut(&normalize(path)) {
+            Some(Node::File(contents)) => {
+                let start = offset as usize;
+                let end = start + data.len();
+                if contents.len() < end {
+                    contents.resize(end, 0);
+                }
+                contents[start..end].copy_from_slice(data);
+                Ok(())
+            }
+            Some(Node::Dir) => Err(Status::INVALID_PARAMETER.into()),
+            None => Err(Status::NOT_FOUND.into()),
+        }
+    }
+
+    fn remove(&self, path: &str) -> uefi::Result {
+        let key = normalize(path);
+        let mut nodes = self.nodes.borrow_mut();
+        let has_children = nodes.keys().any(|k| {
+            k.strip_prefix(key.as_str())
+                .is_some_and(|r| r.starts_with('/'))
+        });
+        if key.is_empty() || has_children {
+            return Err(Status::ACCESS_DENIED.into());
+        }
+        nodes
+            .remove(&key)
+            .map(|_| ())
+            .ok_or(Status::NOT_FOUND.into())
+    }
+
+    fn make_dir(&self, path: &str) -> uefi::Result {
+        let key = normalize(path);
+        if self.is_dir(&key) {
+            return Ok(());
+        }
+        if self.nodes.borrow().contains_key(&key) {
+            return Err(Status::ACCESS_DENIED.into());
+        }
+        self.check_parent(&key)?;
+        self.nodes.borrow_mut().insert(key, Node::Dir);
+        Ok(())
+    }
+}
+
+fn normalize(path: &str) -> String {
+    let mut out = String::new();
+    for part in path.split(['/', '\\']).filter(|p| !p.is_empty()) {
+        if !out.is_empty() {
+            out.push('/');
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+fn entry(name: &str, is_dir: bool, size: u64) -> Entry {
+    let mut e = Entry {
+        name: heapless::String::new(),
+        is_dir,
+        size,
+    };
+    for ch in name.chars() {
+        if e.name.push(ch).is_err() {
+            break;
+        }
+    }
+    e
+}
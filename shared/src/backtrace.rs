@@ -0,0 +1,47 @@
+//! Return addresses from the `rbp` frame chain, for panic reports.
+//!
+//! This needs frame pointers, which `.cargo/config.toml` turns on for the
+//! UEFI target. The walk trusts nothing it reads: it stops at a null or
+//! misaligned frame pointer, at a chain that doesn't move up the stack, and
+//! after [`MAX_DEPTH`] frames.
+
+/// Deepest walk, in case a corrupted chain loops or wanders.
+pub const MAX_DEPTH: usize = 32;
+/// Largest believable distance between two frames.
+#[cfg(target_arch = "x86_64")]
+const MAX_FRAME_BYTES: usize = 1024 * 1024;
+
+/// Fills `out` with return addresses, innermost first, and returns how many
+/// were found.
+#[cfg(target_arch = "x86_64")]
+pub fn capture(out: &mut [usize]) -> usize {
+    let mut rbp: usize;
+    unsafe {
+        core::arch::asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+    }
+    let mut n = 0;
+    while n < out.len().min(MAX_DEPTH) {
+        if rbp == 0 || !rbp.is_multiple_of(core::mem::align_of::<usize>()) {
+            break;
+        }
+        // Each frame starts with the caller's rbp, then the return address.
+        let frame = rbp as *const usize;
+        let (next, ret) = unsafe { (frame.read_volatile(), frame.add(1).read_volatile()) };
+        if ret == 0 {
+            break;
+        }
+        out[n] = ret;
+        n += 1;
+        // The stack grows down, so callers' frames sit at higher addresses.
+        if next <= rbp || next - rbp > MAX_FRAME_BYTES {
+            break;
+        }
+        rbp = next;
+    }
+    n
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub fn capture(_out: &mut [usize]) -> usize {
+    0
+}
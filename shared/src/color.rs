@@ -0,0 +1,92 @@
+//! Backend-neutral colors.
+//!
+//! Commands pick an [`Rgb`] once; it maps to the nearest VGA text color for the
+//! 16-color consoles and to a framebuffer pixel for GOP drawing.
+
+use crate::vga;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0x00, 0x00, 0x00);
+    pub const BLUE: Rgb = Rgb(0x00, 0x00, 0xaa);
+    pub const GREEN: Rgb = Rgb(0x00, 0xaa, 0x00);
+    pub const CYAN: Rgb = Rgb(0x00, 0xaa, 0xaa);
+    pub const RED: Rgb = Rgb(0xaa, 0x00, 0x00);
+    pub const MAGENTA: Rgb = Rgb(0xaa, 0x00, 0xaa);
+    pub const BROWN: Rgb = Rgb(0xaa, 0x55, 0x00);
+    pub const LIGHT_GRAY: Rgb = Rgb(0xaa, 0xaa, 0xaa);
+    pub const DARK_GRAY: Rgb = Rgb(0x55, 0x55, 0x55);
+    pub const LIGHT_BLUE: Rgb = Rgb(0x55, 0x55, 0xff);
+    pub const LIGHT_GREEN: Rgb = Rgb(0x55, 0xff, 0x55);
+    pub const LIGHT_CYAN: Rgb = Rgb(0x55, 0xff, 0xff);
+    pub const LIGHT_RED: Rgb = Rgb(0xff, 0x55, 0x55);
+    pub const LIGHT_MAGENTA: Rgb = Rgb(0xff, 0x55, 0xff);
+    pub const YELLOW: Rgb = Rgb(0xff, 0xff, 0x55);
+    pub const WHITE: Rgb = Rgb(0xff, 0xff, 0xff);
+
+    /// The nearest of the 16 VGA colors by squared RGB distance.
+    pub fn to_vga(self) -> vga::Color {
+        let dist = |c: Rgb| {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(self.0, c.0) + d(self.1, c.1) + d(self.2, c.2)
+        };
+        VGA_PALETTE
+            .iter()
+            .min_by_key(|(_, rgb)| dist(*rgb))
+            .map_or(vga::Color::White, |(color, _)| *color)
+    }
+
+    /// Looks up one of [`NAMES`], ignoring case.
+    pub fn from_name(name: &str) -> Option<Rgb> {
+        NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, rgb)| *rgb)
+    }
+
+    /// Packed as `0x00RRGGBB`, the layout wasabi draws with on BGR framebuffers.
+    pub const fn to_u32(self) -> u32 {
+        (self.0 as u32) << 16 | (self.1 as u32) << 8 | self.2 as u32
+    }
+}
+
+const VGA_PALETTE: [(vga::Color, Rgb); 16] = [
+    (vga::Color::Black, Rgb::BLACK),
+    (vga::Color::Blue, Rgb::BLUE),
+    (vga::Color::Green, Rgb::GREEN),
+    (vga::Color::Cyan, Rgb::CYAN),
+    (vga::Color::Red, Rgb::RED),
+    (vga::Color::Magenta, Rgb::MAGENTA),
+    (vga::Color::Brown, Rgb::BROWN),
+    (vga::Color::LightGray, Rgb::LIGHT_GRAY),
+    (vga::Color::DarkGray, Rgb::DARK_GRAY),
+    (vga::Color::LightBlue, Rgb::LIGHT_BLUE),
+    (vga::Color::LightGreen, Rgb::LIGHT_GREEN),
+    (vga::Color::LightCyan, Rgb::LIGHT_CYAN),
+    (vga::Color::LightRed, Rgb::LIGHT_RED),
+    (vga::Color::LightMagenta, Rgb::LIGHT_MAGENTA),
+    (vga::Color::Yellow, Rgb::YELLOW),
+    (vga::Color::White, Rgb::WHITE),
+];
+
+/// The palette by name, as typed in `theme` and the config file.
+pub const NAMES: [(&str, Rgb); 16] = [
+    ("black", Rgb::BLACK),
+    ("blue", Rgb::BLUE),
+    ("green", Rgb::GREEN),
+    ("cyan", Rgb::CYAN),
+    ("red", Rgb::RED),
+    ("magenta", Rgb::MAGENTA),
+    ("brown", Rgb::BROWN),
+    ("lightgray", Rgb::LIGHT_GRAY),
+    ("darkgray", Rgb::DARK_GRAY),
+    ("lightblue", Rgb::LIGHT_BLUE),
+    ("lightgreen", Rgb::LIGHT_GREEN),
+    ("lightcyan", Rgb::LIGHT_CYAN),
+    ("lightred", Rgb::LIGHT_RED),
+    ("lightmagenta", Rgb::LIGHT_MAGENTA),
+    ("yellow", Rgb::YELLOW),
+    ("white", Rgb::WHITE),
+];
@@ -0,0 +1,248 @@
+//! Boot configuration stored in `\qubit.conf`.
+//!
+//! The file is plain `key=value` lines; blank lines and `#` comments are
+//! ignored, as are unknown keys and values that fail validation, so a damaged
+//! file still boots with defaults.
+
+use core::fmt;
+
+use crate::color::Rgb;
+
+pub const PATH: &str = "\\qubit.conf";
+
+/// Most output lines the `scrollback` key may ask to keep.
+pub const SCROLLBACK_MAX: u16 = 1000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Key {
+    pub name: &'static str,
+    pub help: &'static str,
+    /// Only read while booting, so changes wait for the next boot.
+    pub needs_reboot: bool,
+}
+
+pub const KEYS: &[Key] = &[
+    Key {
+        name: "heap",
+        help: "Kernel heap size in KiB (256..=65536)",
+        needs_reboot: true,
+    },
+    Key {
+        name: "countdown",
+        help: "Seconds to wait before booting (0..=10)",
+        needs_reboot: true,
+    },
+    Key {
+        name: "autorun",
+        help: "Program to start after boot, empty for none",
+        needs_reboot: true,
+    },
+    Key {
+        name: "verbose",
+        help: "Print boot stage messages (true/false)",
+        needs_reboot: true,
+    },
+    Key {
+        name: "ps1",
+        help: "Shell prompt; \\w is replaced by the current directory",
+        needs_reboot: false,
+    },
+    Key {
+        name: "keymap",
+        help: "Keyboard layout (only \"us\" is built in)",
+        needs_reboot: true,
+    },
+    Key {
+        name: "console",
+        help: "Shell output: text (firmware console) or gop (framebuffer terminal)",
+        needs_reboot: true,
+    },
+    Key {
+        name: "theme",
+        help: "Shell text colors as \"<fg> <bg>\" (see `theme`)",
+        needs_reboot: false,
+    },
+    Key {
+        name: "scrollback",
+        help: "Output lines kept for PageUp/PageDown (0..=1000)",
+        needs_reboot: false,
+    },
+];
+
+const KEYMAPS: &[&str] = &["us"];
+const CONSOLES: &[&str] = &["text", "gop"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    UnknownKey,
+    /// The value was malformed or out of range; carries the expected form.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey => write!(f, "unknown key"),
+            ConfigError::Invalid(expected) => write!(f, "expected {}", expected),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub heap_kib: u32,
+    pub countdown: u8,
+    pub autorun: heapless::String<32>,
+    pub verbose: bool,
+    pub ps1: heapless::String<64>,
+    pub keymap: heapless::String<16>,
+    pub console: heapless::String<8>,
+    pub theme: heapless::String<32>,
+    pub scrollback: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut ps1 = heapless::String::new();
+        let _ = ps1.push_str("root@mochi:\\w$ ");
+        let mut keymap = heapless::String::new();
+        let _ = keymap.push_str("us");
+        let mut console = heapless::String::new();
+        let _ = console.push_str("text");
+        let mut theme = heapless::String::new();
+        let _ = theme.push_str("lightgray black");
+        Self {
+            heap_kib: 1024,
+            countdown: 3,
+            autorun: heapless::String::new(),
+            verbose: true,
+            ps1,
+            keymap,
+            console,
+            theme,
+            scrollback: 200,
+        }
+    }
+}
+
+impl Config {
+    /// Reads `text`, starting from the defaults. Bad lines are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        for line in text.lines() {
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                let _ = config.set(key.trim(), value.trim_start());
+            }
+        }
+        config
+    }
+
+    /// Validates and stores one setting. Surrounding whitespace is ignored
+    /// except at the end of `ps1`, where a trailing space is usually wanted.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        if key == "ps1" {
+            self.ps1 = copy_str(value.trim_start(), "at most 64 bytes")?;
+            return Ok(());
+        }
+        let value = value.trim();
+        match key {
+            "heap" => {
+                self.heap_kib = parse_range(value, 256, 65536)
+                    .ok_or(ConfigError::Invalid("a size in KiB between 256 and 65536"))?;
+            }
+            "countdown" => {
+                self.countdown =
+                    parse_range(value, 0, 10).ok_or(ConfigError::Invalid("0 to 10 seconds"))? as u8;
+            }
+            "autorun" => self.autorun = copy_str(value, "a program name")?,
+            "verbose" => {
+                self.verbose = match value {
+                    "true" | "on" | "1" => true,
+                    "false" | "off" | "0" => false,
+                    _ => return Err(ConfigError::Invalid("true or false")),
+                };
+            }
+            "keymap" => {
+                if !KEYMAPS.contains(&value) {
+                    return Err(ConfigError::Invalid("a built-in keymap (us)"));
+                }
+                self.keymap = copy_str(value, "a keymap name")?;
+            }
+            "console" => {
+                if !CONSOLES.contains(&value) {
+                    return Err(ConfigError::Invalid("text or gop"));
+                }
+                self.console = copy_str(value, "text or gop")?;
+            }
+            "theme" => {
+                parse_theme(value).ok_or(ConfigError::Invalid("two color names"))?;
+                self.theme = copy_str(value, "two color names")?;
+            }
+            "scrollback" => {
+                self.scrollback = parse_range(value, 0, SCROLLBACK_MAX.into())
+                    .ok_or(ConfigError::Invalid("0 to 1000 lines"))?
+                    as u16;
+            }
+            _ => return Err(ConfigError::UnknownKey),
+        }
+        Ok(())
+    }
+
+    /// Formats the value of `key` into `out`.
+    pub fn write_value(&self, key: &str, out: &mut dyn fmt::Write) -> Result<(), ConfigError> {
+        let _ = match key {
+            "heap" => write!(out, "{}", self.heap_kib),
+            "countdown" => write!(out, "{}", self.countdown),
+            "autorun" => out.write_str(&self.autorun),
+            "verbose" => write!(out, "{}", self.verbose),
+            "ps1" => out.write_str(&self.ps1),
+            "keymap" => out.write_str(&self.keymap),
+            "console" => out.write_str(&self.console),
+            "theme" => out.write_str(&self.theme),
+            "scrollback" => write!(out, "{}", self.scrollback),
+            _ => return Err(ConfigError::UnknownKey),
+        };
+        Ok(())
+    }
+
+    /// Writes every key as a `key=value` line, the format `parse` reads.
+    pub fn write_to(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        for key in KEYS {
+            out.write_str(key.name)?;
+            out.write_char('=')?;
+            let _ = self.write_value(key.name, out);
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a `theme` value such as `cyan black` into foreground and background.
+pub fn parse_theme(value: &str) -> Option<(Rgb, Rgb)> {
+    let mut names = value.split_whitespace();
+    let fg = Rgb::from_name(names.next()?)?;
+    let bg = Rgb::from_name(names.next()?)?;
+    names.next().is_none().then_some((fg, bg))
+}
+
+pub fn find_key(name: &str) -> Option<&'static Key> {
+    KEYS.iter().find(|k| k.name == name)
+}
+
+fn parse_range(value: &str, min: u32, max: u32) -> Option<u32> {
+    value.parse().ok().filter(|v| (min..=max).contains(v))
+}
+
+fn copy_str<const N: usize>(
+    value: &str,
+    expected: &'static str,
+) -> Result<heapless::String<N>, ConfigError> {
+    let mut out = heapless::String::new();
+    out.push_str(value)
+        .map_err(|_| ConfigError::Invalid(expected))?;
+    Ok(out)
+}
@@ -0,0 +1,28 @@
+use spin::Mutex;
+
+/// How much of the kernel heap is in use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeapStats {
+    pub used: usize,
+    pub free: usize,
+}
+
+impl HeapStats {
+    pub const fn total(&self) -> usize {
+        self.used + self.free
+    }
+}
+
+static REPORTER: Mutex<Option<fn() -> HeapStats>> = Mutex::new(None);
+
+/// Registers how to read the heap's usage. The global allocator lives in the
+/// firmware binary, which calls this once the heap is initialized.
+pub fn set_reporter(report: fn() -> HeapStats) {
+    *REPORTER.lock() = Some(report);
+}
+
+/// Current heap usage, or `None` if no heap has been registered yet.
+pub fn stats() -> Option<HeapStats> {
+    let report = (*REPORTER.lock())?;
+    Some(report())
+}
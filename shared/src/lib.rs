@@ -1,12 +1,53 @@
 #![no_std]
 
+pub mod backtrace;
+pub mod color;
+pub mod config;
+pub mod heap;
+pub mod log;
+pub mod path;
+pub mod pci;
+mod port;
+pub mod ps2;
+pub mod ring;
+pub mod serial;
 pub mod store;
+pub mod text;
 pub mod vga;
 
+/// Prints a line to the UEFI console of `$st`, copied to VGA and serial via [`log::Log`].
 #[macro_export]
 macro_rules! kprintln {
     ($st:expr, $($arg:tt)*) => {{
-        let _ = core::fmt::Write::write_fmt(&mut $st.stdout(), core::format_args!($($arg)*));
-        let _ = core::fmt::Write::write_str(&mut $st.stdout(), "\n");
+        let mut log = $crate::log::Log::console($st.stdout());
+        let _ = core::fmt::Write::write_fmt(&mut log, core::format_args!($($arg)*));
+        let _ = core::fmt::Write::write_str(&mut log, "\n");
     }};
 }
+
+/// Prints to the VGA text buffer in `fg` on `bg`, leaving the current colors as they were.
+#[macro_export]
+macro_rules! vga_print_color {
+    ($fg:expr, $bg:expr, $($arg:tt)*) => {
+        $crate::vga::write_fmt_colored(core::format_args!($($arg)*), $fg, $bg)
+    };
+}
+
+/// Prints to COM1; a no-op until `serial::init` has found the port.
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => {
+        $crate::serial::write_fmt(core::format_args!($($arg)*))
+    };
+}
+
+/// Like [`serial_print!`], with a trailing newline.
+#[macro_export]
+macro_rules! serial_println {
+    () => {
+        $crate::serial_print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::serial::write_fmt(core::format_args!("{}\n", core::format_args!($($arg)*)))
+    };
+}
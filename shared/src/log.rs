@@ -0,0 +1,55 @@
+//! One writer for every output that is up: the UEFI text console when the
+//! caller has one, the VGA text buffer once something has claimed it with
+//! [`use_vga`], and COM1 once [`serial::init`](crate::serial::init) found it.
+//!
+//! Messages that must survive a broken screen, like panics, go through here
+//! so they still end up in a serial log.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{serial, vga};
+
+static VGA: AtomicBool = AtomicBool::new(false);
+
+/// Marks the VGA text buffer as ours to write to (or not). The firmware
+/// owns the screen while its console is in use, so this is off by default.
+pub fn use_vga(on: bool) {
+    VGA.store(on, Ordering::Release);
+}
+
+/// Whether [`use_vga`] has claimed the VGA text buffer.
+pub fn vga_in_use() -> bool {
+    VGA.load(Ordering::Acquire)
+}
+
+pub struct Log<'a> {
+    console: Option<&'a mut dyn fmt::Write>,
+}
+
+impl<'a> Log<'a> {
+    /// Writes to `console` as well as the other outputs.
+    pub fn console(console: &'a mut dyn fmt::Write) -> Self {
+        Self {
+            console: Some(console),
+        }
+    }
+
+    /// Writes to VGA and serial only, for when there is no console.
+    pub fn headless() -> Self {
+        Self { console: None }
+    }
+}
+
+impl fmt::Write for Log<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(console) = self.console.as_mut() {
+            let _ = console.write_str(s);
+        }
+        if vga_in_use() {
+            vga::write_fmt(format_args!("{}", s));
+        }
+        serial::write_str(s);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Path handling for the shells, which see the volume as `/`-separated paths.
+
+/// Joins `rel` onto `base` and resolves `.` and `..`, giving an absolute
+/// `/`-separated path. An empty `rel` means the root, a leading `/` makes it absolute.
+pub fn join_path(base: &str, rel: &str) -> Option<heapless::String<256>> {
+    let is_sep = |c: char| c == '/' || c == '\\';
+    let start = if rel.is_empty() || rel.starts_with(is_sep) {
+        ""
+    } else {
+        base
+    };
+    let mut out = heapless::String::<256>::new();
+    for part in start.split(is_sep).chain(rel.split(is_sep)) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                let parent = out.rfind('/').unwrap_or(0);
+                out.truncate(parent);
+            }
+            _ => {
+                out.push('/').ok()?;
+                out.push_str(part).ok()?;
+            }
+        }
+    }
+    if out.is_empty() {
+        out.push('/').ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_names_join_onto_base() {
+        assert_eq!(join_path("/efi", "boot").unwrap(), "/efi/boot");
+        assert_eq!(join_path("/", "a/b").unwrap(), "/a/b");
+    }
+
+    #[test]
+    fn leading_separator_ignores_base() {
+        assert_eq!(join_path("/efi/boot", "/tmp").unwrap(), "/tmp");
+        assert_eq!(join_path("/efi", "/").unwrap(), "/");
+        assert_eq!(join_path("/efi", "\\x").unwrap(), "/x");
+    }
+
+    #[test]
+    fn dot_dot_pops_but_stops_at_root() {
+        assert_eq!(join_path("/a/b", "..").unwrap(), "/a");
+        assert_eq!(join_path("/a/b", "../c").unwrap(), "/a/c");
+        assert_eq!(join_path("/a", "../../..").unwrap(), "/");
+        assert_eq!(join_path("/", "../x").unwrap(), "/x");
+    }
+
+    #[test]
+    fn dot_and_repeated_separators_vanish() {
+        assert_eq!(join_path("/a", ".").unwrap(), "/a");
+        assert_eq!(join_path("/a", "./b/./c/").unwrap(), "/a/b/c");
+        assert_eq!(join_path("/a", "b//c").unwrap(), "/a/b/c");
+    }
+
+    #[test]
+    fn backslashes_are_separators() {
+        assert_eq!(
+            join_path("\\efi", "boot\\bootx64.efi").unwrap(),
+            "/efi/boot/bootx64.efi"
+        );
+        assert_eq!(join_path("/a/b", "..\\c").unwrap(), "/a/c");
+    }
+
+    #[test]
+    fn empty_rel_is_the_root() {
+        assert_eq!(join_path("/efi/boot", "").unwrap(), "/");
+    }
+
+    #[test]
+    fn overlong_result_is_none() {
+        let a = [b'a'; 300];
+        let long = core::str::from_utf8(&a).unwrap();
+        assert!(join_path("/", long).is_none());
+        assert!(join_path("/", &long[..255]).is_some());
+    }
+}
@@ -0,0 +1,79 @@
+//! PCI enumeration through the legacy configuration ports (`0xCF8`/`0xCFC`).
+//!
+//! Read-only: nothing here changes a device's configuration.
+
+use crate::port::{inl, outl};
+
+const CONFIG_ADDRESS: u16 = 0xcf8;
+const CONFIG_DATA: u16 = 0xcfc;
+/// Vendor ID read back from a slot with nothing in it.
+const NO_VENDOR: u16 = 0xffff;
+/// Header type bit marking a device with functions beyond 0.
+const MULTI_FUNCTION: u8 = 0x80;
+
+/// One present PCI function and the identification fields from its header.
+#[derive(Clone, Copy, Debug)]
+pub struct Function {
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub class: u8,
+    pub subclass: u8,
+    pub prog_if: u8,
+}
+
+/// Reads the aligned dword at `offset` of a function's configuration space.
+pub fn read_config(bus: u8, device: u8, function: u8, offset: u8) -> u32 {
+    let address = 0x8000_0000
+        | (bus as u32) << 16
+        | (device as u32 & 0x1f) << 11
+        | (function as u32 & 0x07) << 8
+        | (offset as u32 & 0xfc);
+    unsafe {
+        outl(CONFIG_ADDRESS, address);
+        inl(CONFIG_DATA)
+    }
+}
+
+fn probe(bus: u8, device: u8, function: u8) -> Option<Function> {
+    let ids = read_config(bus, device, function, 0x00);
+    let vendor_id = ids as u16;
+    if vendor_id == NO_VENDOR {
+        return None;
+    }
+    let class = read_config(bus, device, function, 0x08);
+    Some(Function {
+        bus,
+        device,
+        function,
+        vendor_id,
+        device_id: (ids >> 16) as u16,
+        class: (class >> 24) as u8,
+        subclass: (class >> 16) as u8,
+        prog_if: (class >> 8) as u8,
+    })
+}
+
+/// Calls `f` for every present function on every bus, in bus/device/function
+/// order. Function 0 decides whether functions 1-7 are looked at.
+pub fn scan<F: FnMut(&Function)>(mut f: F) {
+    for bus in 0..=255u8 {
+        for device in 0..32u8 {
+            let Some(first) = probe(bus, device, 0) else {
+                continue;
+            };
+            f(&first);
+            let header = (read_config(bus, device, 0, 0x0c) >> 16) as u8;
+            if header & MULTI_FUNCTION == 0 {
+                continue;
+            }
+            for function in 1..8u8 {
+                if let Some(found) = probe(bus, device, function) {
+                    f(&found);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! x86 port I/O for the legacy devices `vga`, `serial`, `ps2` and `pci` drive.
+
+#[cfg(all(target_arch = "x86_64", not(test)))]
+pub(crate) unsafe fn outb(port: u16, value: u8) {
+    core::arch::asm!(
+        "out dx, al",
+        in("dx") port,
+        in("al") value,
+        options(nomem, nostack, preserves_flags)
+    );
+}
+
+#[cfg(all(target_arch = "x86_64", not(test)))]
+pub(crate) unsafe fn inb(port: u16) -> u8 {
+    let value: u8;
+    core::arch::asm!(
+        "in al, dx",
+        out("al") value,
+        in("dx") port,
+        options(nomem, nostack, preserves_flags)
+    );
+    value
+}
+
+#[cfg(all(target_arch = "x86_64", not(test)))]
+pub(crate) unsafe fn outl(port: u16, value: u32) {
+    core::arch::asm!(
+        "out dx, eax",
+        in("dx") port,
+        in("eax") value,
+        options(nomem, nostack, preserves_flags)
+    );
+}
+
+#[cfg(all(target_arch = "x86_64", not(test)))]
+pub(crate) unsafe fn inl(port: u16) -> u32 {
+    let value: u32;
+    core::arch::asm!(
+        "in eax, dx",
+        out("eax") value,
+        in("dx") port,
+        options(nomem, nostack, preserves_flags)
+    );
+    value
+}
+
+// Without port I/O (other targets, or host unit tests) there are no devices
+// behind the ports: writes vanish and reads come back as zero (or all ones for
+// 32-bit reads, which is what an empty PCI slot returns).
+#[cfg(any(not(target_arch = "x86_64"), test))]
+pub(crate) unsafe fn outb(_port: u16, _value: u8) {}
+
+#[cfg(any(not(target_arch = "x86_64"), test))]
+pub(crate) unsafe fn inb(_port: u16) -> u8 {
+    0
+}
+
+#[cfg(any(not(target_arch = "x86_64"), test))]
+pub(crate) unsafe fn outl(_port: u16, _value: u32) {}
+
+#[cfg(any(not(target_arch = "x86_64"), test))]
+pub(crate) unsafe fn inl(_port: u16) -> u32 {
+    u32::MAX
+}
@@ -0,0 +1,147 @@
+//! Polled PS/2 keyboard on the legacy controller (data `0x60`, status `0x64`).
+//!
+//! The UEFI `Input` protocol goes away with boot services and differs
+//! between firmwares; this reads the controller directly instead. Scancodes
+//! are set 1, decoded with the US layout, and the controller is never
+//! reconfigured, so the firmware's setup (translation on) is what we read.
+
+use spin::Mutex;
+
+use crate::port::inb;
+
+const DATA: u16 = 0x60;
+const STATUS: u16 = 0x64;
+/// Status bit: a byte is waiting in the output buffer.
+const OUTPUT_FULL: u8 = 0x01;
+/// Status bit: the waiting byte came from the mouse, not the keyboard.
+const FROM_AUX: u8 = 0x20;
+/// Prefix for the extended (grey) keys.
+const EXTENDED: u8 = 0xe0;
+/// Set on a scancode when the key is released.
+const RELEASE: u8 = 0x80;
+
+const LEFT_SHIFT: u8 = 0x2a;
+const RIGHT_SHIFT: u8 = 0x36;
+const CTRL: u8 = 0x1d;
+const CAPS_LOCK: u8 = 0x3a;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// A printable character, or a control character for Ctrl+letter.
+    Char(char),
+    Enter,
+    Backspace,
+    Tab,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Delete,
+}
+
+/// US layout for set-1 codes `0x00..0x3a`; 0 means no character.
+const NORMAL: &[u8; 0x3a] =
+    b"\0\x001234567890-=\0\0qwertyuiop[]\0\0asdfghjkl;'`\0\\zxcvbnm,./\0*\0 ";
+const SHIFTED: &[u8; 0x3a] =
+    b"\0\0!@#$%^&*()_+\0\0QWERTYUIOP{}\0\0ASDFGHJKL:\"~\0|ZXCVBNM<>?\0*\0 ";
+
+struct Keyboard {
+    shift: bool,
+    ctrl: bool,
+    caps: bool,
+    extended: bool,
+}
+
+static KEYBOARD: Mutex<Keyboard> = Mutex::new(Keyboard {
+    shift: false,
+    ctrl: false,
+    caps: false,
+    extended: false,
+});
+
+/// Returns the next key press if the controller has one, without waiting.
+/// Releases and modifier keys are consumed and yield `None`.
+pub fn poll() -> Option<Key> {
+    let (status, byte) = unsafe {
+        let status = inb(STATUS);
+        if status & OUTPUT_FULL == 0 {
+            return None;
+        }
+        (status, inb(DATA))
+    };
+    if status & FROM_AUX != 0 {
+        return None;
+    }
+    KEYBOARD.lock().decode(byte)
+}
+
+impl Keyboard {
+    fn decode(&mut self, byte: u8) -> Option<Key> {
+        if byte == EXTENDED {
+            self.extended = true;
+            return None;
+        }
+        let extended = core::mem::take(&mut self.extended);
+        let pressed = byte & RELEASE == 0;
+        let code = byte & !RELEASE;
+        match code {
+            LEFT_SHIFT | RIGHT_SHIFT if !extended => {
+                self.shift = pressed;
+                return None;
+            }
+            CTRL => {
+                self.ctrl = pressed;
+                return None;
+            }
+            CAPS_LOCK if !extended => {
+                self.caps ^= pressed;
+                return None;
+            }
+            _ if !pressed => return None,
+            _ => {}
+        }
+        if extended {
+            return match code {
+                0x1c => Some(Key::Enter),
+                0x47 => Some(Key::Home),
+                0x48 => Some(Key::Up),
+                0x49 => Some(Key::PageUp),
+                0x4b => Some(Key::Left),
+                0x4d => Some(Key::Right),
+                0x4f => Some(Key::End),
+                0x50 => Some(Key::Down),
+                0x51 => Some(Key::PageDown),
+                0x53 => Some(Key::Delete),
+                _ => None,
+            };
+        }
+        match code {
+            0x01 => return Some(Key::Escape),
+            0x0e => return Some(Key::Backspace),
+            0x0f => return Some(Key::Tab),
+            0x1c => return Some(Key::Enter),
+            _ => {}
+        }
+        let table = if self.shift { SHIFTED } else { NORMAL };
+        let mut c = *table.get(code as usize)? as char;
+        if c == '\0' {
+            return None;
+        }
+        if self.caps && c.is_ascii_alphabetic() {
+            c = if self.shift {
+                c.to_ascii_lowercase()
+            } else {
+                c.to_ascii_uppercase()
+            };
+        }
+        if self.ctrl && c.is_ascii_alphabetic() {
+            c = (c.to_ascii_lowercase() as u8 - b'a' + 1) as char;
+        }
+        Some(Key::Char(c))
+    }
+}
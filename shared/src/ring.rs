@@ -0,0 +1,238 @@
+use core::iter::FusedIterator;
+
+/// Fixed-capacity ring buffer. Pushing into a full buffer evicts the oldest item.
+///
+/// Indexing via [`RingBuffer::get`] counts from the newest item (`0`) backwards.
+pub struct RingBuffer<T, const N: usize> {
+    slots: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { None }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends `item`, returning the evicted oldest item if the buffer was full.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if N == 0 {
+            return Some(item);
+        }
+        let evicted = self.slots[self.head].replace(item);
+        self.head = (self.head + 1) % N;
+        if self.len < N {
+            self.len += 1;
+        }
+        evicted
+    }
+
+    /// Removes and returns the oldest item.
+    pub fn pop_oldest(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let tail = (self.head + N - self.len) % N;
+        self.len -= 1;
+        self.slots[tail].take()
+    }
+
+    /// The item `age` pushes ago; `get(0)` is the newest.
+    pub fn get(&self, age: usize) -> Option<&T> {
+        if age >= self.len {
+            return None;
+        }
+        self.slots[(self.head + N - 1 - age) % N].as_ref()
+    }
+
+    pub fn newest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn clear(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = None;
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Iterates from oldest to newest; use `.rev()` for newest first.
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            ring: self,
+            front: self.len,
+            back: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a RingBuffer<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T, const N: usize> {
+    ring: &'a RingBuffer<T, N>,
+    // Ages still to yield lie in `back..front`.
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front -= 1;
+        self.ring.get(self.front)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.front - self.back;
+        (n, Some(n))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for Iter<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back += 1;
+        self.ring.get(self.back - 1)
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Iter<'_, T, N> {}
+
+impl<T, const N: usize> FusedIterator for Iter<'_, T, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_past_capacity_evicts_oldest() {
+        let mut ring = RingBuffer::<u32, 3>::new();
+        assert_eq!(ring.push(1), None);
+        assert_eq!(ring.push(2), None);
+        assert_eq!(ring.push(3), None);
+        assert_eq!(ring.push(4), Some(1));
+        assert_eq!(ring.push(5), Some(2));
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.capacity(), 3);
+    }
+
+    #[test]
+    fn get_counts_back_from_newest_after_wrap() {
+        let mut ring = RingBuffer::<u32, 3>::new();
+        for i in 1..=5 {
+            ring.push(i);
+        }
+        assert_eq!(ring.get(0), Some(&5));
+        assert_eq!(ring.get(1), Some(&4));
+        assert_eq!(ring.get(2), Some(&3));
+        assert_eq!(ring.get(3), None);
+        assert_eq!(ring.newest(), Some(&5));
+    }
+
+    #[test]
+    fn iter_runs_oldest_to_newest() {
+        let mut ring = RingBuffer::<u32, 4>::new();
+        for i in 1..=6 {
+            ring.push(i);
+        }
+        let mut forward = [0; 4];
+        for (slot, &v) in forward.iter_mut().zip(ring.iter()) {
+            *slot = v;
+        }
+        assert_eq!(forward, [3, 4, 5, 6]);
+        let mut backward = [0; 4];
+        for (slot, &v) in backward.iter_mut().zip(ring.iter().rev()) {
+            *slot = v;
+        }
+        assert_eq!(backward, [6, 5, 4, 3]);
+        assert_eq!(ring.iter().len(), 4);
+    }
+
+    #[test]
+    fn iter_meets_in_the_middle() {
+        let mut ring = RingBuffer::<u32, 3>::new();
+        for i in 1..=3 {
+            ring.push(i);
+        }
+        let mut it = ring.iter();
+        assert_eq!(it.next(), Some(&1));
+        assert_eq!(it.next_back(), Some(&3));
+        assert_eq!(it.next(), Some(&2));
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn pop_oldest_takes_from_the_tail() {
+        let mut ring = RingBuffer::<u32, 3>::new();
+        for i in 1..=4 {
+            ring.push(i);
+        }
+        assert_eq!(ring.pop_oldest(), Some(2));
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.get(1), Some(&3));
+        ring.push(5);
+        ring.push(6);
+        assert_eq!(ring.pop_oldest(), Some(4));
+        assert_eq!(ring.pop_oldest(), Some(5));
+        assert_eq!(ring.pop_oldest(), Some(6));
+        assert_eq!(ring.pop_oldest(), None);
+    }
+
+    #[test]
+    fn clear_empties_the_buffer() {
+        let mut ring = RingBuffer::<u32, 2>::new();
+        ring.push(1);
+        ring.push(2);
+        ring.clear();
+        assert!(ring.is_empty());
+        assert_eq!(ring.get(0), None);
+        assert_eq!(ring.push(3), None);
+        assert_eq!(ring.newest(), Some(&3));
+    }
+
+    #[test]
+    fn zero_capacity_hands_items_back() {
+        let mut ring = RingBuffer::<u32, 0>::new();
+        assert_eq!(ring.push(7), Some(7));
+        assert!(ring.is_empty());
+        assert_eq!(ring.get(0), None);
+        assert_eq!(ring.iter().next(), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Output on the first serial port (COM1, a 16550 UART at `0x3F8`).
+//!
+//! QEMU's `-serial stdio` shows this on the host terminal, which makes it the
+//! easiest way to capture logs. Call [`init`] once; until it succeeds every
+//! write is dropped, so callers never have to check for a port first.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use spin::Mutex;
+
+use crate::port::{inb, outb};
+
+const COM1: u16 = 0x3f8;
+const DATA: u16 = COM1;
+const INT_ENABLE: u16 = COM1 + 1;
+const FIFO_CTRL: u16 = COM1 + 2;
+const LINE_CTRL: u16 = COM1 + 3;
+const MODEM_CTRL: u16 = COM1 + 4;
+const LINE_STATUS: u16 = COM1 + 5;
+
+/// 115200 / 1 = 115200 baud.
+const BAUD_DIVISOR: u16 = 1;
+/// Line status bit set while the transmit holding register is empty.
+const THR_EMPTY: u8 = 0x20;
+/// Polls of the line status before a byte is given up on, so a wedged or
+/// absent UART slows output down instead of hanging it.
+const SEND_SPINS: u32 = 100_000;
+
+static READY: AtomicBool = AtomicBool::new(false);
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Programs COM1 for 115200 baud 8N1 with FIFOs on. Returns `false` if the
+/// loopback self-test fails, which usually means there is no UART.
+pub fn init() -> bool {
+    let ok = unsafe {
+        outb(INT_ENABLE, 0x00);
+        outb(LINE_CTRL, 0x80); // DLAB on: the next two ports are the divisor
+        outb(DATA, BAUD_DIVISOR as u8);
+        outb(INT_ENABLE, (BAUD_DIVISOR >> 8) as u8);
+        outb(LINE_CTRL, 0x03); // 8 data bits, no parity, 1 stop bit
+        outb(FIFO_CTRL, 0xc7); // enable and clear FIFOs, 14-byte threshold
+        outb(MODEM_CTRL, 0x1e); // loopback for the self-test
+        outb(DATA, 0xae);
+        let echoed = inb(DATA) == 0xae;
+        outb(MODEM_CTRL, 0x0f); // normal operation, DTR/RTS/OUT1/OUT2
+        echoed
+    };
+    READY.store(ok, Ordering::Release);
+    ok
+}
+
+/// Whether [`init`] found a working port.
+pub fn is_ready() -> bool {
+    READY.load(Ordering::Acquire)
+}
+
+/// Sends one raw byte, waiting for room in the transmitter.
+pub fn write_byte(byte: u8) {
+    if !is_ready() {
+        return;
+    }
+    unsafe {
+        for _ in 0..SEND_SPINS {
+            if inb(LINE_STATUS) & THR_EMPTY != 0 {
+                outb(DATA, byte);
+                return;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Writes text to COM1, turning `\n` into the `\r\n` terminals expect.
+pub struct SerialPort;
+
+impl fmt::Write for SerialPort {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            if byte == b'\n' {
+                write_byte(b'\r');
+            }
+            write_byte(byte);
+        }
+        Ok(())
+    }
+}
+
+/// Writes `s` to COM1 without interleaving with other writers.
+pub fn write_str(s: &str) {
+    let _guard = LOCK.lock();
+    let _ = fmt::Write::write_str(&mut SerialPort, s);
+}
+
+/// Formats `args` to COM1 without interleaving with other writers.
+pub fn write_fmt(args: fmt::Arguments) {
+    let _guard = LOCK.lock();
+    let _ = fmt::Write::write_fmt(&mut SerialPort, args);
+}
@@ -1,5 +1,42 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
 pub const NAME: &str = "Mochi";
 pub const FIRMWARE_NAME: &str = "Zap";
 pub const COMP: &str = "Qubit";
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const PREFIX: &str = "$ ";
+
+#[derive(Clone, Copy, Debug)]
+pub struct Info {
+    pub name: &'static str,
+    pub firmware: &'static str,
+    pub company: &'static str,
+    pub version: &'static str,
+    pub prefix: &'static str,
+}
+
+pub const fn describe() -> Info {
+    Info {
+        name: NAME,
+        firmware: FIRMWARE_NAME,
+        company: COMP,
+        version: VERSION,
+        prefix: PREFIX,
+    }
+}
+
+/// Wall-clock seconds since 1970 when the kernel started; 0 until recorded.
+static BOOT_SECS: AtomicU64 = AtomicU64::new(0);
+
+/// Records when the kernel started, in seconds since 1970.
+pub fn set_boot_time(secs: u64) {
+    BOOT_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// When the kernel started, if [`set_boot_time`] has been called.
+pub fn boot_time() -> Option<u64> {
+    match BOOT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(secs),
+    }
+}
@@ -0,0 +1,81 @@
+//! Small string helpers shared by the shells.
+
+use heapless::Vec;
+
+/// Longest word, in chars, [`edit_distance`] will compare.
+const MAX_WORD: usize = 31;
+
+/// Levenshtein distance over chars, using two rows of scratch space.
+/// Words longer than the scratch rows count as too far apart to compare.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let n = b.chars().count();
+    if n > MAX_WORD || a.chars().count() > MAX_WORD {
+        return usize::MAX;
+    }
+    let mut prev: Vec<usize, { MAX_WORD + 1 }> = (0..=n).collect();
+    let mut cur: Vec<usize, { MAX_WORD + 1 }> = Vec::new();
+    for (i, ca) in a.chars().enumerate() {
+        cur.clear();
+        let _ = cur.push(i + 1);
+        for (j, cb) in b.chars().enumerate() {
+            let cost = usize::from(ca != cb);
+            let best = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+            let _ = cur.push(best);
+        }
+        core::mem::swap(&mut prev, &mut cur);
+    }
+    prev[n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_words_are_zero_apart() {
+        assert_eq!(edit_distance("ls", "ls"), 0);
+        assert_eq!(edit_distance("", ""), 0);
+    }
+
+    #[test]
+    fn common_typos() {
+        assert_eq!(edit_distance("sl", "ls"), 2);
+        assert_eq!(edit_distance("cta", "cat"), 2);
+        assert_eq!(edit_distance("claer", "clear"), 2);
+        assert_eq!(edit_distance("hexdmp", "hexdump"), 1);
+        assert_eq!(edit_distance("hepl", "help"), 2);
+        assert_eq!(edit_distance("ecoh", "echo"), 2);
+        assert_eq!(edit_distance("rebot", "reboot"), 1);
+        assert_eq!(edit_distance("mkdri", "mkdir"), 2);
+    }
+
+    #[test]
+    fn insert_delete_and_substitute_cost_one() {
+        assert_eq!(edit_distance("cat", "cats"), 1);
+        assert_eq!(edit_distance("cats", "cat"), 1);
+        assert_eq!(edit_distance("cat", "cut"), 1);
+        assert_eq!(edit_distance("", "pwd"), 3);
+        assert_eq!(edit_distance("pwd", ""), 3);
+    }
+
+    #[test]
+    fn distance_is_symmetric() {
+        for (a, b) in [("grep", "gerp"), ("theme", "them"), ("df", "cd")] {
+            assert_eq!(edit_distance(a, b), edit_distance(b, a));
+        }
+    }
+
+    #[test]
+    fn counts_chars_not_bytes() {
+        assert_eq!(edit_distance("caf\u{e9}", "cafe"), 1);
+    }
+
+    #[test]
+    fn overlong_words_are_too_far_apart() {
+        let a = [b'a'; MAX_WORD + 1];
+        let long = core::str::from_utf8(&a).unwrap();
+        assert_eq!(edit_distance(long, "a"), usize::MAX);
+        assert_eq!(edit_distance("a", long), usize::MAX);
+        assert_eq!(edit_distance(&long[1..], "a"), MAX_WORD - 1);
+    }
+}
@@ -1,97 +1,530 @@
 use core::fmt;
 use core::ptr;
 
-const BUFFER_WIDTH: usize = 80;
+use spin::Mutex;
+
+use crate::port::{inb, outb};
+
+/// Columns in the text buffer.
+pub const BUFFER_WIDTH: usize = 80;
 const BUFFER_HEIGHT: usize = 25;
+/// Number of cells in the text buffer, i.e. the length of a [`snapshot`].
+pub const SCREEN_CELLS: usize = BUFFER_WIDTH * BUFFER_HEIGHT;
+#[cfg(not(test))]
 const VGA_BUFFER_ADDR: usize = 0xb8000;
-static mut CURSOR_ROW: usize = 0;
-static mut CURSOR_COL: usize = 0;
+const CRTC_INDEX: u16 = 0x3d4;
+const CRTC_DATA: u16 = 0x3d5;
 const DEFAULT_ATTR: u8 = 0x07;
+const TAB_WIDTH: usize = 8;
+const BLINK_INTERVAL_MS: u64 = 500;
 
-#[inline]
-fn write_cell(row: usize, col: usize, byte: u8) {
-    let idx = row * BUFFER_WIDTH + col;
-    let val: u16 = ((DEFAULT_ATTR as u16) << 8) | (byte as u16);
-    let ptr_u16 = (VGA_BUFFER_ADDR as *mut u16).wrapping_add(idx);
-    unsafe { ptr::write_volatile(ptr_u16, val) };
+static WRITER: Mutex<VgaWriter> = Mutex::new(VgaWriter::new());
+
+/// The 16 text-mode colors, in attribute-nibble order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Color {
+    Black = 0,
+    Blue = 1,
+    Green = 2,
+    Cyan = 3,
+    Red = 4,
+    Magenta = 5,
+    Brown = 6,
+    LightGray = 7,
+    DarkGray = 8,
+    LightBlue = 9,
+    LightGreen = 10,
+    LightCyan = 11,
+    LightRed = 12,
+    LightMagenta = 13,
+    Yellow = 14,
+    White = 15,
 }
 
-pub fn clear_screen() {
-    for row in 0..BUFFER_HEIGHT {
-        clear_row(row);
+impl Color {
+    /// The color for a 4-bit attribute nibble; higher bits are ignored.
+    fn from_nibble(n: u8) -> Self {
+        const ALL: [Color; 16] = [
+            Color::Black,
+            Color::Blue,
+            Color::Green,
+            Color::Cyan,
+            Color::Red,
+            Color::Magenta,
+            Color::Brown,
+            Color::LightGray,
+            Color::DarkGray,
+            Color::LightBlue,
+            Color::LightGreen,
+            Color::LightCyan,
+            Color::LightRed,
+            Color::LightMagenta,
+            Color::Yellow,
+            Color::White,
+        ];
+        ALL[(n & 0x0f) as usize]
     }
-    set_cursor_position(0, 0);
 }
 
-pub fn set_cursor_position(row: usize, col: usize) {
-    unsafe {
-        CURSOR_ROW = row;
-        CURSOR_COL = col;
-    }
+/// Where [`VgaWriter::write_byte`] is within an ANSI escape sequence.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// Just saw `ESC`.
+    Start,
+    /// Inside `ESC [`, collecting numeric parameters.
+    Csi,
 }
 
-fn clear_row(row: usize) {
-    for col in 0..BUFFER_WIDTH {
-        write_cell(row, col, b' ');
-    }
+/// ANSI color numbers 0-7 (as in SGR 30-37/40-47) in VGA terms.
+const ANSI_COLORS: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Brown,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightGray,
+];
+
+/// Cursor, color and scroll state for the text buffer, kept behind [`WRITER`].
+struct VgaWriter {
+    row: usize,
+    col: usize,
+    attr: u8,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    blink_shown: bool,
+    blink_last_ms: u64,
+    escape: Escape,
+    params: heapless::Vec<u16, 8>,
+    param: u16,
 }
 
-fn newline() {
-    unsafe {
-        if CURSOR_ROW < BUFFER_HEIGHT - 1 {
-            CURSOR_ROW += 1;
-            CURSOR_COL = 0;
-        } else {
-            for row in 1..BUFFER_HEIGHT {
-                for col in 0..BUFFER_WIDTH {
-                    let from_idx = row * BUFFER_WIDTH + col;
-                    let to_idx = (row - 1) * BUFFER_WIDTH + col;
-                    let from_ptr = (VGA_BUFFER_ADDR as *const u16).wrapping_add(from_idx);
-                    let to_ptr = (VGA_BUFFER_ADDR as *mut u16).wrapping_add(to_idx);
-                    let val = ptr::read_volatile(from_ptr);
-                    ptr::write_volatile(to_ptr, val);
+impl VgaWriter {
+    const fn new() -> Self {
+        Self {
+            row: 0,
+            col: 0,
+            attr: DEFAULT_ATTR,
+            scroll_top: 0,
+            scroll_bottom: BUFFER_HEIGHT - 1,
+            blink_shown: false,
+            blink_last_ms: 0,
+            escape: Escape::None,
+            params: heapless::Vec::new(),
+            param: 0,
+        }
+    }
+
+    fn write_cell(&self, row: usize, col: usize, byte: u8) {
+        let val: u16 = (self.attr as u16) << 8 | (byte as u16);
+        unsafe { ptr::write_volatile(cell_ptr(row, col), val) };
+    }
+
+    fn clear_row(&self, row: usize) {
+        for col in 0..BUFFER_WIDTH {
+            self.write_cell(row, col, b' ');
+        }
+    }
+
+    fn hide_blink(&mut self) {
+        if self.blink_shown {
+            invert_cell(self.row, self.col);
+            self.blink_shown = false;
+        }
+    }
+
+    /// Moves the hardware cursor to `row`/`col`.
+    fn sync_hw_cursor(&self) {
+        let pos = (self.row * BUFFER_WIDTH + self.col.min(BUFFER_WIDTH - 1)) as u16;
+        unsafe {
+            crtc_write(0x0f, pos as u8);
+            crtc_write(0x0e, (pos >> 8) as u8);
+        }
+    }
+
+    fn set_cursor_position(&mut self, row: usize, col: usize) {
+        self.hide_blink();
+        self.row = row.min(BUFFER_HEIGHT - 1);
+        self.col = col.min(BUFFER_WIDTH - 1);
+        self.sync_hw_cursor();
+    }
+
+    fn newline(&mut self) {
+        self.col = 0;
+        if self.row == self.scroll_bottom {
+            self.scroll_up();
+        } else if self.row < BUFFER_HEIGHT - 1 {
+            self.row += 1;
+        }
+    }
+
+    /// Shifts the scroll region up by one row and blanks its last row.
+    fn scroll_up(&self) {
+        for row in self.scroll_top + 1..=self.scroll_bottom {
+            for col in 0..BUFFER_WIDTH {
+                unsafe {
+                    let val = ptr::read_volatile(cell_ptr(row, col));
+                    ptr::write_volatile(cell_ptr(row - 1, col), val);
                 }
             }
-            clear_row(BUFFER_HEIGHT - 1);
-            CURSOR_COL = 0;
         }
+        self.clear_row(self.scroll_bottom);
     }
-}
 
-fn write_byte(byte: u8) {
-    unsafe {
+    /// Feeds `byte` to the escape-sequence parser. Returns `false` if it is
+    /// ordinary output; SGR color codes are applied and anything else in an
+    /// escape sequence is swallowed.
+    fn parse_escape(&mut self, byte: u8) -> bool {
+        match (self.escape, byte) {
+            (Escape::None, 0x1b) => self.escape = Escape::Start,
+            (Escape::None, _) => return false,
+            (Escape::Start, b'[') => {
+                self.escape = Escape::Csi;
+                self.params.clear();
+                self.param = 0;
+            }
+            (Escape::Start, _) => self.escape = Escape::None,
+            (Escape::Csi, b'0'..=b'9') => {
+                self.param = self
+                    .param
+                    .saturating_mul(10)
+                    .saturating_add((byte - b'0') as u16);
+            }
+            (Escape::Csi, b';') => {
+                let _ = self.params.push(self.param);
+                self.param = 0;
+            }
+            (Escape::Csi, 0x40..=0x7e) => {
+                let _ = self.params.push(self.param);
+                if byte == b'm' {
+                    self.apply_sgr();
+                }
+                self.escape = Escape::None;
+            }
+            (Escape::Csi, _) => {}
+        }
+        true
+    }
+
+    /// Applies the collected `ESC [ ... m` parameters to `attr`.
+    fn apply_sgr(&mut self) {
+        for &p in self.params.iter() {
+            match p {
+                0 => self.attr = DEFAULT_ATTR,
+                30..=37 => {
+                    self.attr = (self.attr & 0xf0) | ANSI_COLORS[(p - 30) as usize] as u8;
+                }
+                40..=47 => {
+                    self.attr = (self.attr & 0x0f) | (ANSI_COLORS[(p - 40) as usize] as u8) << 4;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        if self.parse_escape(byte) {
+            return;
+        }
+        self.hide_blink();
         match byte {
-            b'\n' => {
-                newline();
+            b'\n' => self.newline(),
+            b'\r' => self.col = 0,
+            b'\t' => {
+                if self.col >= BUFFER_WIDTH {
+                    self.newline();
+                }
+                let stop = ((self.col / TAB_WIDTH + 1) * TAB_WIDTH).min(BUFFER_WIDTH);
+                while self.col < stop {
+                    self.write_cell(self.row, self.col, b' ');
+                    self.col += 1;
+                }
+            }
+            0x08 => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    self.write_cell(self.row, self.col, b' ');
+                }
             }
             b => {
-                if CURSOR_COL >= BUFFER_WIDTH {
-                    newline();
+                if self.col >= BUFFER_WIDTH {
+                    self.newline();
                 }
-                write_cell(CURSOR_ROW, CURSOR_COL, b);
-                CURSOR_COL += 1;
+                self.write_cell(self.row, self.col, b);
+                self.col += 1;
             }
         }
+        self.sync_hw_cursor();
     }
 }
 
-struct Writer;
-
-impl fmt::Write for Writer {
+impl fmt::Write for VgaWriter {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         for byte in s.bytes() {
-            write_byte(byte);
+            self.write_byte(byte);
         }
         Ok(())
     }
 }
 
+/// Sets the colors used for text written from now on.
+pub fn set_color(fg: Color, bg: Color) {
+    WRITER.lock().attr = attr(fg, bg);
+}
+
+pub fn reset_color() {
+    WRITER.lock().attr = DEFAULT_ATTR;
+}
+
+fn attr(fg: Color, bg: Color) -> u8 {
+    ((bg as u8) << 4) | fg as u8
+}
+
+#[inline]
+fn cell_ptr(row: usize, col: usize) -> *mut u16 {
+    buffer_base().wrapping_add(row * BUFFER_WIDTH + col)
+}
+
+#[cfg(not(test))]
+fn buffer_base() -> *mut u16 {
+    VGA_BUFFER_ADDR as *mut u16
+}
+
+/// Unit tests run on the host, so the writer draws into RAM instead.
+#[cfg(test)]
+fn buffer_base() -> *mut u16 {
+    ptr::addr_of_mut!(tests::CELLS) as *mut u16
+}
+
+/// Returns the character and foreground/background colors shown at a cell.
+/// Positions off the screen read as a blank in the default colors.
+pub fn read_cell(row: usize, col: usize) -> (u8, Color, Color) {
+    if row >= BUFFER_HEIGHT || col >= BUFFER_WIDTH {
+        return (b' ', Color::LightGray, Color::Black);
+    }
+    let val = unsafe { ptr::read_volatile(cell_ptr(row, col)) };
+    let attr = (val >> 8) as u8;
+    (
+        val as u8,
+        Color::from_nibble(attr),
+        Color::from_nibble(attr >> 4),
+    )
+}
+
+fn invert_cell(row: usize, col: usize) {
+    if row >= BUFFER_HEIGHT || col >= BUFFER_WIDTH {
+        return;
+    }
+    let ptr = cell_ptr(row, col);
+    unsafe {
+        let val = ptr::read_volatile(ptr);
+        let attr = (val >> 8) as u8;
+        let swapped = attr.rotate_left(4);
+        ptr::write_volatile(ptr, ((swapped as u16) << 8) | (val & 0x00ff));
+    }
+}
+
+/// Toggles an inverted block under the cursor every `BLINK_INTERVAL_MS`.
+/// Only the attribute byte is touched, so the character underneath survives.
+pub fn tick_cursor(now_ms: u64) {
+    let mut w = WRITER.lock();
+    if now_ms < w.blink_last_ms {
+        w.blink_last_ms = now_ms;
+    }
+    if now_ms - w.blink_last_ms < BLINK_INTERVAL_MS {
+        return;
+    }
+    w.blink_last_ms = now_ms;
+    invert_cell(w.row, w.col);
+    w.blink_shown = !w.blink_shown;
+}
+
+/// Shows the hardware cursor as a block spanning scanlines `start..=end`
+/// of each 16-line cell (e.g. `14, 15` for an underline).
+pub fn enable_cursor(start: u8, end: u8) {
+    unsafe {
+        let shape = crtc_read(0x0a);
+        crtc_write(0x0a, (shape & 0xc0) | (start & 0x1f));
+        let shape = crtc_read(0x0b);
+        crtc_write(0x0b, (shape & 0xe0) | (end & 0x1f));
+    }
+    WRITER.lock().sync_hw_cursor();
+}
+
+/// Hides the hardware cursor.
+pub fn disable_cursor() {
+    unsafe { crtc_write(0x0a, 0x20) };
+}
+
+/// Writes CRTC register `index`. Only touches the standard VGA ports, so it
+/// is sound wherever the text buffer at `VGA_BUFFER_ADDR` is.
+unsafe fn crtc_write(index: u8, value: u8) {
+    outb(CRTC_INDEX, index);
+    outb(CRTC_DATA, value);
+}
+
+unsafe fn crtc_read(index: u8) -> u8 {
+    outb(CRTC_INDEX, index);
+    inb(CRTC_DATA)
+}
+
+/// Blanks every row in the current color and homes the cursor.
+pub fn clear_screen() {
+    let mut w = WRITER.lock();
+    w.hide_blink();
+    for row in 0..BUFFER_HEIGHT {
+        w.clear_row(row);
+    }
+    w.set_cursor_position(0, 0);
+}
+
+/// Moves the cursor, clamping out-of-range positions to the last row/column.
+pub fn set_cursor_position(row: usize, col: usize) {
+    WRITER.lock().set_cursor_position(row, col);
+}
+
+/// Restricts scrolling to rows `top..=bottom`; rows outside stay put.
+/// `bottom` is clamped to the last row, and an empty region is ignored.
+pub fn set_scroll_region(top: usize, bottom: usize) {
+    let bottom = bottom.min(BUFFER_HEIGHT - 1);
+    if top >= bottom {
+        return;
+    }
+    let mut w = WRITER.lock();
+    w.scroll_top = top;
+    w.scroll_bottom = bottom;
+}
+
+/// Current cursor position as `(row, col)`, for restoring after a [`restore`].
+pub fn cursor_position() -> (usize, usize) {
+    let w = WRITER.lock();
+    (w.row, w.col)
+}
+
+/// Copies every raw cell (character and attribute) off the screen.
+pub fn snapshot() -> [u16; SCREEN_CELLS] {
+    let mut w = WRITER.lock();
+    w.hide_blink();
+    let mut cells = [0u16; SCREEN_CELLS];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        *cell = unsafe { ptr::read_volatile(cell_ptr(0, i)) };
+    }
+    cells
+}
+
+/// Puts back cells taken with [`snapshot`]. The cursor is left where it is;
+/// pair with [`cursor_position`] and [`set_cursor_position`] to restore it too.
+pub fn restore(cells: &[u16; SCREEN_CELLS]) {
+    let mut w = WRITER.lock();
+    w.hide_blink();
+    for (i, &cell) in cells.iter().enumerate() {
+        unsafe { ptr::write_volatile(cell_ptr(0, i), cell) };
+    }
+}
+
 pub fn write_fmt(args: fmt::Arguments) {
-    let mut w = Writer;
-    let _ = fmt::Write::write_fmt(&mut w, args);
+    let _ = fmt::Write::write_fmt(&mut *WRITER.lock(), args);
+}
+
+/// Writes `s` in the given colors, then goes back to the previous ones.
+pub fn write_colored(s: &str, fg: Color, bg: Color) {
+    write_fmt_colored(format_args!("{}", s), fg, bg);
+}
+
+/// Like [`write_fmt`], but in the given colors; used by `vga_print_color!`.
+pub fn write_fmt_colored(args: fmt::Arguments, fg: Color, bg: Color) {
+    let mut w = WRITER.lock();
+    let saved = w.attr;
+    w.attr = attr(fg, bg);
+    let _ = fmt::Write::write_fmt(&mut *w, args);
+    w.attr = saved;
 }
 
 pub fn writeln_fmt(args: fmt::Arguments) {
-    write_fmt(args);
-    write_byte(b'\n');
+    let mut w = WRITER.lock();
+    let _ = fmt::Write::write_fmt(&mut *w, args);
+    w.write_byte(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) static mut CELLS: [u16; SCREEN_CELLS] = [0; SCREEN_CELLS];
+
+    /// Tests share `WRITER` and `CELLS`, so they take turns.
+    static SCREEN: Mutex<()> = Mutex::new(());
+
+    fn fresh_screen() -> spin::MutexGuard<'static, ()> {
+        let guard = SCREEN.lock();
+        *WRITER.lock() = VgaWriter::new();
+        unsafe { *ptr::addr_of_mut!(CELLS) = [0; SCREEN_CELLS] };
+        guard
+    }
+
+    fn row_text(row: usize, len: usize) -> heapless::String<BUFFER_WIDTH> {
+        let mut s = heapless::String::new();
+        for col in 0..len {
+            let _ = s.push(read_cell(row, col).0 as char);
+        }
+        s
+    }
+
+    #[test]
+    fn writeln_fmt_writes_at_the_cursor_and_moves_down() {
+        let _screen = fresh_screen();
+        writeln_fmt(format_args!("hi {}", 42));
+        assert_eq!(row_text(0, 5), "hi 42");
+        assert_eq!(read_cell(0, 0), (b'h', Color::LightGray, Color::Black));
+        assert_eq!(cursor_position(), (1, 0));
+    }
+
+    #[test]
+    fn writeln_fmt_applies_sgr_colors() {
+        let _screen = fresh_screen();
+        writeln_fmt(format_args!("\x1b[31;44mx\x1b[0my"));
+        assert_eq!(read_cell(0, 0), (b'x', Color::Red, Color::Blue));
+        assert_eq!(read_cell(0, 1), (b'y', Color::LightGray, Color::Black));
+    }
+
+    #[test]
+    fn writeln_fmt_scrolls_at_the_bottom() {
+        let _screen = fresh_screen();
+        for i in 0..BUFFER_HEIGHT + 1 {
+            writeln_fmt(format_args!("{}", i % 10));
+        }
+        assert_eq!(read_cell(0, 0).0, b'2');
+        assert_eq!(read_cell(BUFFER_HEIGHT - 2, 0).0, b'5');
+        assert_eq!(read_cell(BUFFER_HEIGHT - 1, 0).0, b' ');
+        assert_eq!(cursor_position(), (BUFFER_HEIGHT - 1, 0));
+    }
+
+    #[test]
+    fn long_lines_wrap_to_the_next_row() {
+        let _screen = fresh_screen();
+        for _ in 0..BUFFER_WIDTH {
+            write_fmt(format_args!("a"));
+        }
+        writeln_fmt(format_args!("b"));
+        assert_eq!(read_cell(0, BUFFER_WIDTH - 1).0, b'a');
+        assert_eq!(read_cell(1, 0).0, b'b');
+        assert_eq!(cursor_position(), (2, 0));
+    }
+
+    #[test]
+    fn read_cell_off_screen_is_blank() {
+        let _screen = fresh_screen();
+        assert_eq!(
+            read_cell(BUFFER_HEIGHT, 0),
+            (b' ', Color::LightGray, Color::Black)
+        );
+        assert_eq!(
+            read_cell(0, BUFFER_WIDTH),
+            (b' ', Color::LightGray, Color::Black)
+        );
+    }
 }
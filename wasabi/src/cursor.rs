@@ -0,0 +1,93 @@
+//! Software mouse pointer.
+//!
+//! GOP has no hardware cursor, so the pointer is an ordinary sprite: before
+//! drawing it, [`Cursor`] saves the pixels it covers and puts them back when it
+//! moves, leaving whatever was underneath untouched.
+
+use alloc::vec::Vec;
+use uefi::proto::console::gop::GraphicsOutput;
+
+use crate::{draw_pixel, restore_region, save_region};
+
+/// Arrow rows, most significant bit leftmost: (outline, fill).
+#[rustfmt::skip]
+const ARROW: [(u8, u8); 12] = [
+    (0x80, 0x00),
+    (0xc0, 0x00),
+    (0xa0, 0x40),
+    (0x90, 0x60),
+    (0x88, 0x70),
+    (0x84, 0x78),
+    (0x82, 0x7c),
+    (0x81, 0x7e),
+    (0x9e, 0x60),
+    (0xa0, 0x40),
+    (0xc0, 0x00),
+    (0x80, 0x00),
+];
+
+pub struct Cursor {
+    x: usize,
+    y: usize,
+    fill: u32,
+    outline: u32,
+    /// Pixels under the sprite while it is on screen.
+    saved: Option<Vec<u32>>,
+}
+
+impl Cursor {
+    pub const WIDTH: usize = 8;
+    pub const HEIGHT: usize = ARROW.len();
+
+    /// A hidden cursor; the first `move_to` shows it.
+    pub fn new(fill: u32, outline: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            fill,
+            outline,
+            saved: None,
+        }
+    }
+
+    /// Where the tip of the arrow is.
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// Restores the old location and draws the arrow with its tip at
+    /// (`x`, `y`), clamped to the screen.
+    pub fn move_to(&mut self, gop: &mut GraphicsOutput, x: usize, y: usize) {
+        self.hide(gop);
+        let (sw, sh) = gop.current_mode_info().resolution();
+        self.x = x.min(sw.saturating_sub(1));
+        self.y = y.min(sh.saturating_sub(1));
+        self.saved = Some(save_region(gop, self.x, self.y, Self::WIDTH, Self::HEIGHT));
+        for (row, &(outline, fill)) in ARROW.iter().enumerate() {
+            for col in 0..Self::WIDTH {
+                let bit = 0x80 >> col;
+                let color = if outline & bit != 0 {
+                    self.outline
+                } else if fill & bit != 0 {
+                    self.fill
+                } else {
+                    continue;
+                };
+                draw_pixel(gop, self.x + col, self.y + row, color);
+            }
+        }
+    }
+
+    /// Takes the arrow off screen, restoring what it covered.
+    pub fn hide(&mut self, gop: &mut GraphicsOutput) {
+        if let Some(saved) = self.saved.take() {
+            restore_region(gop, self.x, self.y, Self::WIDTH, Self::HEIGHT, &saved);
+        }
+    }
+
+    /// Drops the saved pixels without restoring them, for when the screen
+    /// under the cursor has been redrawn and they are stale.
+    pub fn forget(&mut self) {
+        self.saved = None;
+    }
+}
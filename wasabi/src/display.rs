@@ -0,0 +1,290 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use shared::color::Rgb;
+use uefi::proto::console::gop::{BltOp, BltRegion, GraphicsOutput, PixelBitmask, PixelFormat};
+
+use crate::font::Font;
+use crate::Rect;
+
+/// Geometry of the active GOP mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FbInfo {
+    pub width: usize,
+    pub height: usize,
+    pub stride: usize,
+    pub format: PixelFormat,
+    /// Channel masks, present only for `PixelFormat::Bitmask` modes.
+    pub mask: Option<PixelBitmask>,
+}
+
+impl FbInfo {
+    pub fn from_gop(gop: &GraphicsOutput) -> Self {
+        let mode = gop.current_mode_info();
+        let (width, height) = mode.resolution();
+        Self {
+            width,
+            height,
+            stride: mode.stride(),
+            format: mode.pixel_format(),
+            mask: mode.pixel_bitmask(),
+        }
+    }
+
+    /// No linear framebuffer; output has to go through `GraphicsOutput::blt`.
+    pub fn is_blt_only(&self) -> bool {
+        self.format == PixelFormat::BltOnly
+    }
+}
+
+/// Conversion of backend-neutral colors into the active mode's pixel layout.
+pub trait ToGopNative {
+    /// The `u32` to hand to wasabi's drawing functions for this mode.
+    fn to_gop_native(&self, info: &FbInfo) -> u32;
+}
+
+impl ToGopNative for Rgb {
+    fn to_gop_native(&self, info: &FbInfo) -> u32 {
+        let Rgb(r, g, b) = *self;
+        crate::pack_color(info.format, info.mask, r, g, b)
+    }
+}
+
+/// An off-screen RAM surface, packed with no padding between rows.
+///
+/// Draw a whole frame into it, then call `present` once; drawing straight to
+/// the live framebuffer shows half-finished frames as flicker. Drawing calls
+/// grow a single dirty rectangle and `present` copies just that region,
+/// honouring the framebuffer stride, which keeps small updates cheap on large
+/// modes. Needs the `alloc` feature.
+pub struct BackBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+    dirty: Option<Rect>,
+}
+
+impl BackBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+            dirty: None,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    pub fn dirty(&self) -> Option<Rect> {
+        self.dirty
+    }
+
+    /// Adds the `w` x `h` region at (`x`, `y`) to what the next `present`
+    /// copies. Everything marked during a frame coalesces into one bounding box.
+    pub fn mark_dirty(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        self.mark_rect_dirty(Rect::new(x, y, w, h));
+    }
+
+    /// Adds `rect` (clipped to the buffer) to the region copied by the next `present`.
+    pub fn mark_rect_dirty(&mut self, rect: Rect) {
+        let rect = rect.intersect(&Rect::new(0, 0, self.width, self.height));
+        if rect.is_empty() {
+            return;
+        }
+        self.dirty = Some(match self.dirty {
+            Some(d) => d.union(&rect),
+            None => rect,
+        });
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty = None;
+    }
+
+    pub fn clear(&mut self, color: u32) {
+        self.pixels.fill(color);
+        self.mark_dirty(0, 0, self.width, self.height);
+    }
+
+    pub fn draw_pixel(&mut self, x: usize, y: usize, color: u32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        self.pixels[y * self.width + x] = color;
+        self.mark_dirty(x, y, 1, 1);
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: u32) {
+        if x >= self.width || y >= self.height || w == 0 || h == 0 {
+            return;
+        }
+        let w = w.min(self.width - x);
+        let h = h.min(self.height - y);
+        for row in y..y + h {
+            let start = row * self.width + x;
+            self.pixels[start..start + w].fill(color);
+        }
+        self.mark_dirty(x, y, w, h);
+    }
+
+    /// Moves the pixels in `src` so its top-left corner lands at (`x`, `y`).
+    /// The regions may overlap, which is how text scrolls.
+    pub fn copy_rect(&mut self, src: Rect, x: usize, y: usize) {
+        let src = src.intersect(&Rect::new(0, 0, self.width, self.height));
+        let w = src.w.min(self.width.saturating_sub(x));
+        let h = src.h.min(self.height.saturating_sub(y));
+        if w == 0 || h == 0 {
+            return;
+        }
+        let width = self.width;
+        let pixels = &mut self.pixels;
+        let mut copy_row = |i: usize| {
+            let from = (src.y + i) * width + src.x;
+            pixels.copy_within(from..from + w, (y + i) * width + x);
+        };
+        // Walk away from the destination so no row is overwritten before it is read.
+        if y <= src.y {
+            (0..h).for_each(&mut copy_row);
+        } else {
+            (0..h).rev().for_each(&mut copy_row);
+        }
+        self.mark_dirty(x, y, w, h);
+    }
+
+    /// Paints one bitmap-font cell: set bits in `fg`, the rest of the
+    /// `advance() x height()` cell in `bg`. Cells that don't fit are skipped.
+    pub fn draw_char(&mut self, x: usize, y: usize, c: char, font: &dyn Font, fg: u32, bg: u32) {
+        let (w, h) = (font.advance(), font.height());
+        if x + w > self.width || y + h > self.height {
+            return;
+        }
+        let glyph = font.glyph(c);
+        for gy in 0..h {
+            let bits = glyph.get(gy).copied().unwrap_or(0);
+            let start = (y + gy) * self.width + x;
+            for (gx, px) in self.pixels[start..start + w].iter_mut().enumerate() {
+                *px = if gx < 8 && bits & (0x80 >> gx) != 0 {
+                    fg
+                } else {
+                    bg
+                };
+            }
+        }
+        self.mark_dirty(x, y, w, h);
+    }
+
+    /// Copies the dirty region to the live framebuffer and resets it.
+    pub fn present(&mut self, gop: &mut GraphicsOutput) {
+        if let Some(rect) = self.dirty.take() {
+            self.copy_out(gop, rect);
+        }
+    }
+
+    /// Copies the whole buffer regardless of what is marked dirty.
+    pub fn present_all(&mut self, gop: &mut GraphicsOutput) {
+        self.dirty = None;
+        self.copy_out(gop, Rect::new(0, 0, self.width, self.height));
+    }
+
+    fn copy_out(&self, gop: &mut GraphicsOutput, rect: Rect) {
+        let info = FbInfo::from_gop(gop);
+        let rect = rect.intersect(&Rect::new(0, 0, info.width, info.height));
+        if rect.is_empty() {
+            return;
+        }
+        if info.is_blt_only() {
+            let _ = gop.blt(BltOp::BufferToVideo {
+                buffer: crate::as_blt_pixels(&self.pixels),
+                src: BltRegion::SubRectangle {
+                    coords: (rect.x, rect.y),
+                    px_stride: self.width,
+                },
+                dest: (rect.x, rect.y),
+                dims: (rect.w, rect.h),
+            });
+            return;
+        }
+        let mut fb = gop.frame_buffer();
+        for row in rect.y..rect.y + rect.h {
+            let start = row * self.width + rect.x;
+            let src = &self.pixels[start..start + rect.w];
+            crate::span(&mut fb, row * info.stride + rect.x, rect.w).copy_from_slice(src);
+        }
+    }
+}
+
+/// A back buffer sized to the current mode. Draw into it, then `present` once
+/// per frame so the live framebuffer only receives what changed. BltOnly
+/// modes are presented with `blt` instead of framebuffer writes.
+pub struct Display {
+    info: FbInfo,
+    back: BackBuffer,
+}
+
+impl Display {
+    pub fn new(gop: &GraphicsOutput) -> Self {
+        let info = FbInfo::from_gop(gop);
+        Self {
+            info,
+            back: BackBuffer::new(info.width, info.height),
+        }
+    }
+
+    pub fn info(&self) -> &FbInfo {
+        &self.info
+    }
+
+    pub fn back(&self) -> &BackBuffer {
+        &self.back
+    }
+
+    pub fn back_mut(&mut self) -> &mut BackBuffer {
+        &mut self.back
+    }
+
+    pub fn clear(&mut self, color: u32) {
+        self.back.clear(color);
+    }
+
+    pub fn draw_pixel(&mut self, x: usize, y: usize, color: u32) {
+        self.back.draw_pixel(x, y, color);
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: u32) {
+        self.back.fill_rect(x, y, w, h, color);
+    }
+
+    pub fn copy_rect(&mut self, src: Rect, x: usize, y: usize) {
+        self.back.copy_rect(src, x, y);
+    }
+
+    pub fn draw_char(&mut self, x: usize, y: usize, c: char, font: &dyn Font, fg: u32, bg: u32) {
+        self.back.draw_char(x, y, c, font, fg, bg);
+    }
+
+    pub fn mark_dirty(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        self.back.mark_dirty(x, y, w, h);
+    }
+
+    pub fn mark_rect_dirty(&mut self, rect: Rect) {
+        self.back.mark_rect_dirty(rect);
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.back.clear_dirty();
+    }
+
+    pub fn present(&mut self, gop: &mut GraphicsOutput) {
+        self.back.present(gop);
+    }
+}
@@ -0,0 +1,128 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use uefi::proto::console::gop::GraphicsOutput;
+
+use crate::font::{self, Font};
+
+pub enum DrawCmd {
+    FillRect {
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        color: u32,
+    },
+    Line {
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        color: u32,
+    },
+    Text {
+        x: usize,
+        y: usize,
+        text: String,
+        color: u32,
+        font: &'static dyn Font,
+    },
+    Blit {
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        pixels: Vec<u32>,
+    },
+}
+
+/// Records drawing primitives so a whole frame can be replayed against a single
+/// opened `GraphicsOutput`, instead of reopening the protocol for every call.
+#[derive(Default)]
+pub struct DrawList {
+    cmds: Vec<DrawCmd>,
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        Self { cmds: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cmds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
+    pub fn push(&mut self, cmd: DrawCmd) {
+        self.cmds.push(cmd);
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: u32) {
+        self.push(DrawCmd::FillRect { x, y, w, h, color });
+    }
+
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: u32) {
+        self.push(DrawCmd::Line {
+            x0,
+            y0,
+            x1,
+            y1,
+            color,
+        });
+    }
+
+    pub fn text(&mut self, x: usize, y: usize, text: &str, color: u32) {
+        self.text_with(x, y, text, color, &font::DEFAULT);
+    }
+
+    pub fn text_with(
+        &mut self,
+        x: usize,
+        y: usize,
+        text: &str,
+        color: u32,
+        font: &'static dyn Font,
+    ) {
+        self.push(DrawCmd::Text {
+            x,
+            y,
+            text: String::from(text),
+            color,
+            font,
+        });
+    }
+
+    pub fn blit(&mut self, x: usize, y: usize, w: usize, h: usize, pixels: Vec<u32>) {
+        self.push(DrawCmd::Blit { x, y, w, h, pixels });
+    }
+
+    pub fn clear(&mut self) {
+        self.cmds.clear();
+    }
+
+    /// Replays every recorded command in order and empties the list.
+    pub fn flush(&mut self, gop: &mut GraphicsOutput) {
+        for cmd in self.cmds.drain(..) {
+            match cmd {
+                DrawCmd::FillRect { x, y, w, h, color } => crate::fill_rect(gop, x, y, w, h, color),
+                DrawCmd::Line {
+                    x0,
+                    y0,
+                    x1,
+                    y1,
+                    color,
+                } => crate::draw_line(gop, x0, y0, x1, y1, color),
+                DrawCmd::Text {
+                    x,
+                    y,
+                    text,
+                    color,
+                    font,
+                } => crate::draw_text_with(gop, x, y, &text, color, font),
+                DrawCmd::Blit { x, y, w, h, pixels } => crate::blit(gop, x, y, w, h, &pixels),
+            }
+        }
+    }
+}
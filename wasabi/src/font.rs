@@ -0,0 +1,470 @@
+//! Built-in fonts.
+//!
+//! The bitmap fonts cover printable ASCII. Glyphs store one byte per row with
+//! the most significant bit leftmost. The 8x16 set is rasterized from DejaVu
+//! Sans Mono Bold; the 8x8 set is a downsampled copy for dense displays.
+//! [`VectorFont`] is a small stroke font for large text.
+
+use uefi::proto::console::gop::GraphicsOutput;
+
+mod vector;
+
+pub use vector::VectorFont;
+
+pub trait Font {
+    /// Horizontal distance between the origins of consecutive glyphs.
+    fn advance(&self) -> usize;
+    fn height(&self) -> usize;
+    /// Row bitmaps for `c`, `height()` bytes long. Empty for fonts that
+    /// override [`Font::draw_glyph`] and have no bitmap form.
+    fn glyph(&self, c: char) -> &[u8];
+
+    /// Draws `c` with its cell's top-left corner at (`x`, `y`).
+    fn draw_glyph(&self, gop: &mut GraphicsOutput, x: usize, y: usize, c: char, color: u32) {
+        for (gy, bits) in self.glyph(c).iter().enumerate() {
+            for gx in 0..8 {
+                if bits & (0x80 >> gx) != 0 {
+                    crate::draw_pixel(gop, x + gx, y + gy, color);
+                }
+            }
+        }
+    }
+}
+
+pub struct Default8x16;
+pub struct Small8x8;
+
+pub static DEFAULT: Default8x16 = Default8x16;
+pub static SMALL: Small8x8 = Small8x8;
+
+impl Font for Default8x16 {
+    fn advance(&self) -> usize {
+        8
+    }
+
+    fn height(&self) -> usize {
+        16
+    }
+
+    fn glyph(&self, c: char) -> &[u8] {
+        static MISSING: [u8; 16] = [
+            0x00, 0x00, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        index(c).map_or(&MISSING, |i| &GLYPHS_8X16[i])
+    }
+}
+
+impl Font for Small8x8 {
+    fn advance(&self) -> usize {
+        8
+    }
+
+    fn height(&self) -> usize {
+        8
+    }
+
+    fn glyph(&self, c: char) -> &[u8] {
+        static MISSING: [u8; 8] = [0x00, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x00];
+        index(c).map_or(&MISSING, |i| &GLYPHS_8X8[i])
+    }
+}
+
+const FIRST: u8 = b' ';
+const LAST: u8 = b'~';
+const GLYPH_COUNT: usize = (LAST - FIRST + 1) as usize;
+
+fn index(c: char) -> Option<usize> {
+    match c {
+        ' '..='~' => Some((c as u8 - FIRST) as usize),
+        _ => None,
+    }
+}
+
+#[rustfmt::skip]
+static GLYPHS_8X16: [[u8; 16]; GLYPH_COUNT] = [
+    // ' '
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '!'
+    [0x00, 0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // '"'
+    [0x00, 0x00, 0x66, 0x66, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '#'
+    [0x00, 0x00, 0x12, 0x12, 0x16, 0x7f, 0x34, 0x24, 0xfe, 0x68, 0x48, 0x48, 0x00, 0x00, 0x00, 0x00],
+    // '$'
+    [0x00, 0x10, 0x10, 0x7c, 0xd4, 0xd0, 0xf8, 0x3c, 0x16, 0x16, 0xd6, 0x7c, 0x10, 0x10, 0x00, 0x00],
+    // '%'
+    [0x00, 0x00, 0x60, 0x90, 0x90, 0x63, 0x0c, 0x30, 0xc6, 0x09, 0x09, 0x06, 0x00, 0x00, 0x00, 0x00],
+    // '&'
+    [0x00, 0x00, 0x38, 0x60, 0x60, 0x20, 0x70, 0xf6, 0xde, 0xde, 0xcc, 0x7e, 0x00, 0x00, 0x00, 0x00],
+    // quote
+    [0x00, 0x00, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '('
+    [0x00, 0x0c, 0x18, 0x18, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x18, 0x18, 0x0c, 0x00, 0x00, 0x00],
+    // ')'
+    [0x00, 0x30, 0x18, 0x18, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x18, 0x18, 0x30, 0x00, 0x00, 0x00],
+    // '*'
+    [0x00, 0x00, 0x10, 0xd6, 0x7c, 0x7c, 0xd6, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '+'
+    [0x00, 0x00, 0x00, 0x18, 0x18, 0x18, 0xff, 0xff, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // ','
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x10, 0x20, 0x00, 0x00, 0x00],
+    // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3c, 0x3c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '.'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // '/'
+    [0x00, 0x00, 0x06, 0x0c, 0x0c, 0x0c, 0x18, 0x18, 0x30, 0x30, 0x60, 0x60, 0x60, 0xc0, 0x00, 0x00],
+    // '0'
+    [0x00, 0x00, 0x38, 0x6c, 0xc6, 0xc6, 0xd6, 0xd6, 0xc6, 0xc6, 0x6c, 0x38, 0x00, 0x00, 0x00, 0x00],
+    // '1'
+    [0x00, 0x00, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00, 0x00, 0x00, 0x00],
+    // '2'
+    [0x00, 0x00, 0x7c, 0x86, 0x06, 0x06, 0x0c, 0x1c, 0x38, 0x70, 0xe0, 0xfe, 0x00, 0x00, 0x00, 0x00],
+    // '3'
+    [0x00, 0x00, 0x7c, 0x86, 0x06, 0x06, 0x38, 0x0e, 0x06, 0x06, 0x8e, 0x7c, 0x00, 0x00, 0x00, 0x00],
+    // '4'
+    [0x00, 0x00, 0x0c, 0x1c, 0x3c, 0x6c, 0x4c, 0xcc, 0xfe, 0x0c, 0x0c, 0x0c, 0x00, 0x00, 0x00, 0x00],
+    // '5'
+    [0x00, 0x00, 0xfc, 0xc0, 0xc0, 0xf8, 0x8c, 0x06, 0x06, 0x06, 0x8c, 0x78, 0x00, 0x00, 0x00, 0x00],
+    // '6'
+    [0x00, 0x00, 0x38, 0x64, 0xc0, 0xfc, 0xc6, 0xc6, 0xc6, 0xc6, 0x46, 0x3c, 0x00, 0x00, 0x00, 0x00],
+    // '7'
+    [0x00, 0x00, 0xfe, 0x06, 0x0e, 0x0c, 0x1c, 0x18, 0x18, 0x30, 0x30, 0x60, 0x00, 0x00, 0x00, 0x00],
+    // '8'
+    [0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0x38, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00],
+    // '9'
+    [0x00, 0x00, 0x78, 0xc4, 0xc6, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x4c, 0x38, 0x00, 0x00, 0x00, 0x00],
+    // ':'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // ';'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x10, 0x20, 0x00, 0x00, 0x00],
+    // '<'
+    [0x00, 0x00, 0x00, 0x00, 0x02, 0x1e, 0x78, 0xc0, 0x78, 0x1e, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '='
+    [0x00, 0x00, 0x00, 0x00, 0xfe, 0xfe, 0x00, 0x00, 0xfe, 0xfe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '>'
+    [0x00, 0x00, 0x00, 0x00, 0x80, 0xf0, 0x3c, 0x06, 0x3c, 0xf0, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '?'
+    [0x00, 0x00, 0x3c, 0x46, 0x06, 0x0c, 0x18, 0x30, 0x30, 0x00, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00],
+    // '@'
+    [0x00, 0x00, 0x1e, 0x63, 0x41, 0x9f, 0xb3, 0xa1, 0xa1, 0xb3, 0x9f, 0x40, 0x21, 0x1f, 0x00, 0x00],
+    // 'A'
+    [0x00, 0x00, 0x38, 0x38, 0x38, 0x28, 0x6c, 0x6c, 0x7c, 0x6c, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00],
+    // 'B'
+    [0x00, 0x00, 0xfc, 0xc6, 0xc6, 0xc6, 0xf8, 0xc6, 0xc6, 0xc6, 0xc6, 0xfc, 0x00, 0x00, 0x00, 0x00],
+    // 'C'
+    [0x00, 0x00, 0x3c, 0x62, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0x62, 0x3c, 0x00, 0x00, 0x00, 0x00],
+    // 'D'
+    [0x00, 0x00, 0xf8, 0xcc, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xcc, 0xf8, 0x00, 0x00, 0x00, 0x00],
+    // 'E'
+    [0x00, 0x00, 0xfe, 0xc0, 0xc0, 0xc0, 0xfc, 0xc0, 0xc0, 0xc0, 0xc0, 0xfe, 0x00, 0x00, 0x00, 0x00],
+    // 'F'
+    [0x00, 0x00, 0xfe, 0xc0, 0xc0, 0xc0, 0xfc, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0x00, 0x00, 0x00, 0x00],
+    // 'G'
+    [0x00, 0x00, 0x3c, 0x62, 0xc0, 0xc0, 0xc0, 0xce, 0xc6, 0xc6, 0x66, 0x3e, 0x00, 0x00, 0x00, 0x00],
+    // 'H'
+    [0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00],
+    // 'I'
+    [0x00, 0x00, 0x7e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00, 0x00, 0x00, 0x00],
+    // 'J'
+    [0x00, 0x00, 0x1e, 0x06, 0x06, 0x06, 0x06, 0x06, 0x06, 0x06, 0x86, 0x7c, 0x00, 0x00, 0x00, 0x00],
+    // 'K'
+    [0x00, 0x00, 0x63, 0x66, 0x6c, 0x7c, 0x7c, 0x7c, 0x6e, 0x66, 0x63, 0x63, 0x00, 0x00, 0x00, 0x00],
+    // 'L'
+    [0x00, 0x00, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xfe, 0x00, 0x00, 0x00, 0x00],
+    // 'M'
+    [0x00, 0x00, 0xee, 0xee, 0xee, 0xee, 0xfe, 0xd6, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00],
+    // 'N'
+    [0x00, 0x00, 0xe6, 0xe6, 0xe6, 0xf6, 0xd6, 0xd6, 0xde, 0xce, 0xce, 0xce, 0x00, 0x00, 0x00, 0x00],
+    // 'O'
+    [0x00, 0x00, 0x38, 0x6c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x00, 0x00, 0x00, 0x00],
+    // 'P'
+    [0x00, 0x00, 0xfc, 0xc6, 0xc6, 0xc6, 0xc6, 0xfc, 0xc0, 0xc0, 0xc0, 0xc0, 0x00, 0x00, 0x00, 0x00],
+    // 'Q'
+    [0x00, 0x00, 0x38, 0x6c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x3c, 0x0c, 0x04, 0x00, 0x00],
+    // 'R'
+    [0x00, 0x00, 0x7e, 0x63, 0x63, 0x63, 0x63, 0x7c, 0x66, 0x63, 0x63, 0x61, 0x00, 0x00, 0x00, 0x00],
+    // 'S'
+    [0x00, 0x00, 0x7c, 0xc2, 0xc0, 0xc0, 0xf8, 0x3c, 0x0e, 0x06, 0x86, 0x7c, 0x00, 0x00, 0x00, 0x00],
+    // 'T'
+    [0x00, 0x00, 0xff, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // 'U'
+    [0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00],
+    // 'V'
+    [0x00, 0x00, 0xc6, 0xc6, 0x6c, 0x6c, 0x6c, 0x6c, 0x6c, 0x28, 0x38, 0x38, 0x00, 0x00, 0x00, 0x00],
+    // 'W'
+    [0x00, 0x00, 0xc3, 0xc3, 0xc3, 0xdb, 0x5b, 0x5a, 0x7e, 0x66, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00],
+    // 'X'
+    [0x00, 0x00, 0xc6, 0x6c, 0x6c, 0x38, 0x38, 0x38, 0x38, 0x6c, 0x6c, 0xc6, 0x00, 0x00, 0x00, 0x00],
+    // 'Y'
+    [0x00, 0x00, 0xc3, 0x66, 0x66, 0x3c, 0x3c, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // 'Z'
+    [0x00, 0x00, 0xfe, 0x06, 0x0c, 0x1c, 0x18, 0x30, 0x70, 0x60, 0xc0, 0xfe, 0x00, 0x00, 0x00, 0x00],
+    // '['
+    [0x00, 0x3c, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3c, 0x00, 0x00, 0x00],
+    // backslash
+    [0x00, 0x00, 0xc0, 0x40, 0x60, 0x20, 0x30, 0x30, 0x18, 0x18, 0x08, 0x0c, 0x04, 0x06, 0x00, 0x00],
+    // ']'
+    [0x00, 0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3c, 0x00, 0x00, 0x00],
+    // '^'
+    [0x00, 0x00, 0x18, 0x3c, 0x66, 0xc3, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '_'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x00],
+    // '`'
+    [0x30, 0x18, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 'a'
+    [0x00, 0x00, 0x00, 0x00, 0x1c, 0x26, 0x06, 0x3e, 0x66, 0x66, 0x66, 0x3e, 0x00, 0x00, 0x00, 0x00],
+    // 'b'
+    [0x00, 0x60, 0x60, 0x60, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x00, 0x00, 0x00, 0x00],
+    // 'c'
+    [0x00, 0x00, 0x00, 0x00, 0x1c, 0x32, 0x60, 0x60, 0x60, 0x60, 0x32, 0x1c, 0x00, 0x00, 0x00, 0x00],
+    // 'd'
+    [0x00, 0x06, 0x06, 0x06, 0x3e, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x00, 0x00, 0x00, 0x00],
+    // 'e'
+    [0x00, 0x00, 0x00, 0x00, 0x3c, 0x26, 0x66, 0x7e, 0x60, 0x60, 0x32, 0x3c, 0x00, 0x00, 0x00, 0x00],
+    // 'f'
+    [0x00, 0x0e, 0x18, 0x18, 0x7e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // 'g'
+    [0x00, 0x00, 0x00, 0x00, 0x3e, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x06, 0x06, 0x3c, 0x00],
+    // 'h'
+    [0x00, 0x60, 0x60, 0x60, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00],
+    // 'i'
+    [0x00, 0x18, 0x18, 0x00, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0xfe, 0x00, 0x00, 0x00, 0x00],
+    // 'j'
+    [0x00, 0x0c, 0x0c, 0x00, 0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x78, 0x00],
+    // 'k'
+    [0x00, 0x60, 0x60, 0x60, 0x64, 0x6c, 0x78, 0x78, 0x78, 0x6c, 0x6c, 0x66, 0x00, 0x00, 0x00, 0x00],
+    // 'l'
+    [0x00, 0xf0, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x1e, 0x00, 0x00, 0x00, 0x00],
+    // 'm'
+    [0x00, 0x00, 0x00, 0x00, 0xff, 0xdb, 0xdb, 0xdb, 0xdb, 0xdb, 0xdb, 0xdb, 0x00, 0x00, 0x00, 0x00],
+    // 'n'
+    [0x00, 0x00, 0x00, 0x00, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00],
+    // 'o'
+    [0x00, 0x00, 0x00, 0x00, 0x3c, 0x24, 0x66, 0x66, 0x66, 0x66, 0x24, 0x3c, 0x00, 0x00, 0x00, 0x00],
+    // 'p'
+    [0x00, 0x00, 0x00, 0x00, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x60, 0x60, 0x60, 0x00],
+    // 'q'
+    [0x00, 0x00, 0x00, 0x00, 0x3e, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x06, 0x06, 0x06, 0x00],
+    // 'r'
+    [0x00, 0x00, 0x00, 0x00, 0x7e, 0x70, 0x60, 0x60, 0x60, 0x60, 0x60, 0x60, 0x00, 0x00, 0x00, 0x00],
+    // 's'
+    [0x00, 0x00, 0x00, 0x00, 0x3c, 0x62, 0x60, 0x78, 0x1e, 0x06, 0x46, 0x3c, 0x00, 0x00, 0x00, 0x00],
+    // 't'
+    [0x00, 0x00, 0x30, 0x30, 0xfe, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x1e, 0x00, 0x00, 0x00, 0x00],
+    // 'u'
+    [0x00, 0x00, 0x00, 0x00, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x00, 0x00, 0x00, 0x00],
+    // 'v'
+    [0x00, 0x00, 0x00, 0x00, 0x66, 0x66, 0x66, 0x24, 0x3c, 0x3c, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00],
+    // 'w'
+    [0x00, 0x00, 0x00, 0x00, 0xc3, 0xc3, 0xdb, 0x5a, 0x5a, 0x5a, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00],
+    // 'x'
+    [0x00, 0x00, 0x00, 0x00, 0x66, 0x3c, 0x3c, 0x18, 0x18, 0x3c, 0x3c, 0x66, 0x00, 0x00, 0x00, 0x00],
+    // 'y'
+    [0x00, 0x00, 0x00, 0x00, 0x66, 0x66, 0x2c, 0x3c, 0x3c, 0x38, 0x18, 0x18, 0x18, 0x30, 0x70, 0x00],
+    // 'z'
+    [0x00, 0x00, 0x00, 0x00, 0x7e, 0x06, 0x0c, 0x1c, 0x38, 0x30, 0x60, 0x7e, 0x00, 0x00, 0x00, 0x00],
+    // '{'
+    [0x00, 0x0e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x60, 0x18, 0x18, 0x18, 0x18, 0x18, 0x0e, 0x00, 0x00],
+    // '|'
+    [0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+    // '}'
+    [0x00, 0x70, 0x18, 0x18, 0x18, 0x18, 0x18, 0x06, 0x18, 0x18, 0x18, 0x18, 0x18, 0x70, 0x00, 0x00],
+    // '~'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x72, 0xfe, 0x8c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+];
+
+#[rustfmt::skip]
+static GLYPHS_8X8: [[u8; 8]; GLYPH_COUNT] = [
+    // ' '
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '!'
+    [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+    // '"'
+    [0x66, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '#'
+    [0x12, 0x16, 0x7f, 0x34, 0xfe, 0x68, 0x48, 0x00],
+    // '$'
+    [0x10, 0xfc, 0xd0, 0xfc, 0x16, 0xd6, 0x7c, 0x10],
+    // '%'
+    [0x60, 0x90, 0x63, 0x3c, 0xc6, 0x09, 0x06, 0x00],
+    // '&'
+    [0x38, 0x60, 0x20, 0xf6, 0xde, 0xde, 0x7e, 0x00],
+    // quote
+    [0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '('
+    [0x18, 0x38, 0x30, 0x30, 0x30, 0x38, 0x18, 0x00],
+    // ')'
+    [0x18, 0x1c, 0x0c, 0x0c, 0x0c, 0x1c, 0x18, 0x00],
+    // '*'
+    [0x10, 0xfe, 0x7c, 0xd6, 0x00, 0x00, 0x00, 0x00],
+    // '+'
+    [0x00, 0x18, 0x18, 0xff, 0x18, 0x18, 0x00, 0x00],
+    // ','
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x10, 0x00],
+    // '-'
+    [0x00, 0x00, 0x00, 0x3c, 0x3c, 0x00, 0x00, 0x00],
+    // '.'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00],
+    // '/'
+    [0x06, 0x0c, 0x0c, 0x18, 0x30, 0x70, 0x60, 0xc0],
+    // '0'
+    [0x38, 0xee, 0xc6, 0xd6, 0xc6, 0xee, 0x38, 0x00],
+    // '1'
+    [0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00],
+    // '2'
+    [0x7c, 0x86, 0x06, 0x1c, 0x38, 0xf0, 0xfe, 0x00],
+    // '3'
+    [0x7c, 0x86, 0x06, 0x3e, 0x06, 0x8e, 0x7c, 0x00],
+    // '4'
+    [0x0c, 0x3c, 0x6c, 0xcc, 0xfe, 0x0c, 0x0c, 0x00],
+    // '5'
+    [0xfc, 0xc0, 0xf8, 0x8e, 0x06, 0x8e, 0x78, 0x00],
+    // '6'
+    [0x38, 0xe4, 0xfc, 0xc6, 0xc6, 0xc6, 0x3c, 0x00],
+    // '7'
+    [0xfe, 0x0e, 0x0c, 0x1c, 0x18, 0x30, 0x60, 0x00],
+    // '8'
+    [0x7c, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0x7c, 0x00],
+    // '9'
+    [0x78, 0xc6, 0xc6, 0xc6, 0x7e, 0x4e, 0x38, 0x00],
+    // ':'
+    [0x00, 0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00],
+    // ';'
+    [0x00, 0x00, 0x18, 0x18, 0x00, 0x18, 0x10, 0x00],
+    // '<'
+    [0x00, 0x02, 0x1e, 0xf8, 0x78, 0x1e, 0x00, 0x00],
+    // '='
+    [0x00, 0xfe, 0xfe, 0x00, 0xfe, 0xfe, 0x00, 0x00],
+    // '>'
+    [0x00, 0x80, 0xf0, 0x3e, 0x3c, 0xf0, 0x00, 0x00],
+    // '?'
+    [0x3c, 0x46, 0x0c, 0x38, 0x30, 0x30, 0x30, 0x00],
+    // '@'
+    [0x1e, 0x63, 0x9f, 0xb3, 0xa1, 0xbf, 0x40, 0x1f],
+    // 'A'
+    [0x38, 0x38, 0x28, 0x6c, 0x7c, 0xee, 0xc6, 0x00],
+    // 'B'
+    [0xfc, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xfc, 0x00],
+    // 'C'
+    [0x3c, 0xe2, 0xc0, 0xc0, 0xc0, 0xe2, 0x3c, 0x00],
+    // 'D'
+    [0xf8, 0xce, 0xc6, 0xc6, 0xc6, 0xce, 0xf8, 0x00],
+    // 'E'
+    [0xfe, 0xc0, 0xc0, 0xfc, 0xc0, 0xc0, 0xfe, 0x00],
+    // 'F'
+    [0xfe, 0xc0, 0xc0, 0xfc, 0xc0, 0xc0, 0xc0, 0x00],
+    // 'G'
+    [0x3c, 0xe2, 0xc0, 0xce, 0xc6, 0xe6, 0x3e, 0x00],
+    // 'H'
+    [0xc6, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0x00],
+    // 'I'
+    [0x7e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00],
+    // 'J'
+    [0x1e, 0x06, 0x06, 0x06, 0x06, 0x86, 0x7c, 0x00],
+    // 'K'
+    [0x63, 0x6e, 0x7c, 0x7c, 0x6e, 0x67, 0x63, 0x00],
+    // 'L'
+    [0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xfe, 0x00],
+    // 'M'
+    [0xee, 0xee, 0xee, 0xfe, 0xc6, 0xc6, 0xc6, 0x00],
+    // 'N'
+    [0xe6, 0xe6, 0xf6, 0xd6, 0xde, 0xce, 0xce, 0x00],
+    // 'O'
+    [0x38, 0xee, 0xc6, 0xc6, 0xc6, 0xee, 0x38, 0x00],
+    // 'P'
+    [0xfc, 0xc6, 0xc6, 0xfe, 0xc0, 0xc0, 0xc0, 0x00],
+    // 'Q'
+    [0x38, 0xee, 0xc6, 0xc6, 0xc6, 0xee, 0x3c, 0x04],
+    // 'R'
+    [0x7e, 0x63, 0x63, 0x7f, 0x66, 0x63, 0x61, 0x00],
+    // 'S'
+    [0x7c, 0xc2, 0xc0, 0xfc, 0x0e, 0x86, 0x7c, 0x00],
+    // 'T'
+    [0xff, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+    // 'U'
+    [0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00],
+    // 'V'
+    [0xc6, 0xee, 0x6c, 0x6c, 0x6c, 0x38, 0x38, 0x00],
+    // 'W'
+    [0xc3, 0xc3, 0xdb, 0x5b, 0x7e, 0x66, 0x66, 0x00],
+    // 'X'
+    [0xc6, 0x6c, 0x38, 0x38, 0x38, 0x6c, 0xc6, 0x00],
+    // 'Y'
+    [0xc3, 0x66, 0x3c, 0x3c, 0x18, 0x18, 0x18, 0x00],
+    // 'Z'
+    [0xfe, 0x0e, 0x1c, 0x38, 0x70, 0xe0, 0xfe, 0x00],
+    // '['
+    [0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x00],
+    // backslash
+    [0xc0, 0x60, 0x20, 0x30, 0x18, 0x18, 0x0c, 0x06],
+    // ']'
+    [0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x00],
+    // '^'
+    [0x18, 0x7e, 0xc3, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '_'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff],
+    // '`'
+    [0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 'a'
+    [0x00, 0x1c, 0x26, 0x3e, 0x66, 0x66, 0x3e, 0x00],
+    // 'b'
+    [0x60, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x00],
+    // 'c'
+    [0x00, 0x1c, 0x32, 0x60, 0x60, 0x72, 0x1c, 0x00],
+    // 'd'
+    [0x06, 0x3e, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x00],
+    // 'e'
+    [0x00, 0x3c, 0x26, 0x7e, 0x60, 0x72, 0x3c, 0x00],
+    // 'f'
+    [0x18, 0x7e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+    // 'g'
+    [0x00, 0x3e, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x3e],
+    // 'h'
+    [0x60, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x00],
+    // 'i'
+    [0x18, 0x78, 0x18, 0x18, 0x18, 0x18, 0xfe, 0x00],
+    // 'j'
+    [0x0c, 0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x7c],
+    // 'k'
+    [0x60, 0x64, 0x6c, 0x78, 0x78, 0x6c, 0x66, 0x00],
+    // 'l'
+    [0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x1e, 0x00],
+    // 'm'
+    [0x00, 0xff, 0xdb, 0xdb, 0xdb, 0xdb, 0xdb, 0x00],
+    // 'n'
+    [0x00, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x00],
+    // 'o'
+    [0x00, 0x3c, 0x24, 0x66, 0x66, 0x66, 0x3c, 0x00],
+    // 'p'
+    [0x00, 0x7c, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x60],
+    // 'q'
+    [0x00, 0x3e, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x06],
+    // 'r'
+    [0x00, 0x7e, 0x70, 0x60, 0x60, 0x60, 0x60, 0x00],
+    // 's'
+    [0x00, 0x3c, 0x62, 0x78, 0x1e, 0x46, 0x3c, 0x00],
+    // 't'
+    [0x30, 0xfe, 0x30, 0x30, 0x30, 0x30, 0x1e, 0x00],
+    // 'u'
+    [0x00, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3e, 0x00],
+    // 'v'
+    [0x00, 0x66, 0x66, 0x66, 0x3c, 0x3c, 0x18, 0x00],
+    // 'w'
+    [0x00, 0xc3, 0xc3, 0xdb, 0x5a, 0x7e, 0x66, 0x00],
+    // 'x'
+    [0x00, 0x66, 0x3c, 0x3c, 0x18, 0x3c, 0x66, 0x00],
+    // 'y'
+    [0x00, 0x66, 0x66, 0x3c, 0x3c, 0x38, 0x18, 0x70],
+    // 'z'
+    [0x00, 0x7e, 0x06, 0x1c, 0x38, 0x70, 0x7e, 0x00],
+    // '{'
+    [0x18, 0x18, 0x18, 0x78, 0x18, 0x18, 0x18, 0x0e],
+    // '|'
+    [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18],
+    // '}'
+    [0x18, 0x18, 0x18, 0x1e, 0x18, 0x18, 0x18, 0x70],
+    // '~'
+    [0x00, 0x00, 0x00, 0xfe, 0x8c, 0x00, 0x00, 0x00],
+];
@@ -0,0 +1,111 @@
+//! Stroke font for large text (clocks, splash screens).
+//!
+//! Glyphs are polylines on a 4x6 grid with the origin at the top left, drawn
+//! with [`crate::draw_line`] and scaled to any height. Coverage is limited to
+//! digits, `:`, space and `A`-`Z`; lowercase maps to uppercase and anything
+//! else draws as an empty box.
+
+use uefi::proto::console::gop::GraphicsOutput;
+
+use super::Font;
+
+const GRID_W: i32 = 4;
+const GRID_H: i32 = 6;
+
+type Stroke = &'static [(u8, u8)];
+
+pub struct VectorFont {
+    size: usize,
+}
+
+impl VectorFont {
+    /// A font whose glyphs are `size` pixels tall.
+    pub const fn new(size: usize) -> Self {
+        Self { size }
+    }
+
+    /// Stroke width in pixels, grown with the size so big text isn't spindly.
+    fn weight(&self) -> i32 {
+        (self.size / 16).max(1) as i32
+    }
+}
+
+impl Font for VectorFont {
+    fn advance(&self) -> usize {
+        self.size * (GRID_W + 2) as usize / GRID_H as usize
+    }
+
+    fn height(&self) -> usize {
+        self.size
+    }
+
+    fn glyph(&self, _c: char) -> &[u8] {
+        &[]
+    }
+
+    fn draw_glyph(&self, gop: &mut GraphicsOutput, x: usize, y: usize, c: char, color: u32) {
+        let size = self.size as i32;
+        let weight = self.weight();
+        // Leave room for the pen on the right and bottom edges.
+        let span = size - weight;
+        let px = |gx: u8| x as i32 + gx as i32 * span / GRID_H;
+        let py = |gy: u8| y as i32 + gy as i32 * span / GRID_H;
+
+        for stroke in strokes(c) {
+            for pair in stroke.windows(2) {
+                let (x0, y0) = (px(pair[0].0), py(pair[0].1));
+                let (x1, y1) = (px(pair[1].0), py(pair[1].1));
+                for d in 0..weight * weight {
+                    let (dx, dy) = (d % weight, d / weight);
+                    crate::draw_line(gop, x0 + dx, y0 + dy, x1 + dx, y1 + dy, color);
+                }
+            }
+        }
+    }
+}
+
+#[rustfmt::skip]
+fn strokes(c: char) -> &'static [Stroke] {
+    match c.to_ascii_uppercase() {
+        ' ' => &[],
+        '0' => &[&[(1, 0), (3, 0), (4, 1), (4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0)], &[(4, 1), (0, 5)]],
+        '1' => &[&[(1, 1), (2, 0), (2, 6)], &[(1, 6), (3, 6)]],
+        '2' => &[&[(0, 1), (1, 0), (3, 0), (4, 1), (4, 2), (0, 6), (4, 6)]],
+        '3' => &[&[(0, 0), (4, 0), (2, 2), (3, 2), (4, 3), (4, 5), (3, 6), (1, 6), (0, 5)]],
+        '4' => &[&[(3, 6), (3, 0), (0, 4), (4, 4)]],
+        '5' => &[&[(4, 0), (0, 0), (0, 2), (3, 2), (4, 3), (4, 5), (3, 6), (1, 6), (0, 5)]],
+        '6' => &[&[(3, 0), (1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 3), (3, 2), (0, 2)]],
+        '7' => &[&[(0, 0), (4, 0), (1, 6)]],
+        '8' => &[&[(1, 3), (0, 2), (0, 1), (1, 0), (3, 0), (4, 1), (4, 2), (3, 3), (1, 3)],
+                 &[(1, 3), (0, 4), (0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (3, 3)]],
+        '9' => &[&[(4, 3), (1, 3), (0, 2), (0, 1), (1, 0), (3, 0), (4, 1), (4, 5), (3, 6), (1, 6)]],
+        ':' => &[&[(2, 1), (2, 2)], &[(2, 4), (2, 5)]],
+        'A' => &[&[(0, 6), (0, 2), (2, 0), (4, 2), (4, 6)], &[(0, 4), (4, 4)]],
+        'B' => &[&[(0, 3), (3, 3), (4, 2), (4, 1), (3, 0), (0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3)]],
+        'C' => &[&[(4, 1), (3, 0), (1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5)]],
+        'D' => &[&[(0, 0), (0, 6), (2, 6), (4, 4), (4, 2), (2, 0), (0, 0)]],
+        'E' => &[&[(4, 0), (0, 0), (0, 6), (4, 6)], &[(0, 3), (3, 3)]],
+        'F' => &[&[(4, 0), (0, 0), (0, 6)], &[(0, 3), (3, 3)]],
+        'G' => &[&[(4, 1), (3, 0), (1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 3), (2, 3)]],
+        'H' => &[&[(0, 0), (0, 6)], &[(4, 0), (4, 6)], &[(0, 3), (4, 3)]],
+        'I' => &[&[(1, 0), (3, 0)], &[(2, 0), (2, 6)], &[(1, 6), (3, 6)]],
+        'J' => &[&[(4, 0), (4, 5), (3, 6), (1, 6), (0, 5)]],
+        'K' => &[&[(0, 0), (0, 6)], &[(4, 0), (0, 4)], &[(1, 3), (4, 6)]],
+        'L' => &[&[(0, 0), (0, 6), (4, 6)]],
+        'M' => &[&[(0, 6), (0, 0), (2, 3), (4, 0), (4, 6)]],
+        'N' => &[&[(0, 6), (0, 0), (4, 6), (4, 0)]],
+        'O' => &[&[(1, 0), (3, 0), (4, 1), (4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0)]],
+        'P' => &[&[(0, 6), (0, 0), (3, 0), (4, 1), (4, 2), (3, 3), (0, 3)]],
+        'Q' => &[&[(1, 0), (3, 0), (4, 1), (4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0)], &[(2, 4), (4, 6)]],
+        'R' => &[&[(0, 6), (0, 0), (3, 0), (4, 1), (4, 2), (3, 3), (0, 3)], &[(2, 3), (4, 6)]],
+        'S' => &[&[(4, 1), (3, 0), (1, 0), (0, 1), (0, 2), (1, 3), (3, 3), (4, 4), (4, 5), (3, 6), (1, 6), (0, 5)]],
+        'T' => &[&[(0, 0), (4, 0)], &[(2, 0), (2, 6)]],
+        'U' => &[&[(0, 0), (0, 5), (1, 6), (3, 6), (4, 5), (4, 0)]],
+        'V' => &[&[(0, 0), (2, 6), (4, 0)]],
+        'W' => &[&[(0, 0), (1, 6), (2, 3), (3, 6), (4, 0)]],
+        'X' => &[&[(0, 0), (4, 6)], &[(4, 0), (0, 6)]],
+        'Y' => &[&[(0, 0), (2, 3), (4, 0)], &[(2, 3), (2, 6)]],
+        'Z' => &[&[(0, 0), (4, 0), (0, 6), (4, 6)]],
+        _ => &[&[(0, 0), (4, 0), (4, 6), (0, 6), (0, 0)]],
+    }
+}
@@ -1,8 +1,91 @@
 #![no_std]
 
-use uefi::proto::console::gop::GraphicsOutput;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod cursor;
+#[cfg(feature = "alloc")]
+mod display;
+#[cfg(feature = "alloc")]
+mod draw_list;
+pub mod fixed;
+pub mod font;
+#[cfg(feature = "alloc")]
+mod plasma;
+#[cfg(feature = "alloc")]
+mod terminal;
+
+#[cfg(feature = "alloc")]
+pub use cursor::Cursor;
+#[cfg(feature = "alloc")]
+pub use display::{BackBuffer, Display, FbInfo, ToGopNative};
+#[cfg(feature = "alloc")]
+pub use draw_list::{DrawCmd, DrawList};
+pub use font::{Font, VectorFont};
+#[cfg(feature = "alloc")]
+pub use plasma::fill_plasma;
+#[cfg(feature = "alloc")]
+pub use terminal::Terminal;
+
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use uefi::proto::console::gop::{
+    BltOp, BltPixel, BltRegion, FrameBuffer, GraphicsOutput, PixelBitmask, PixelFormat,
+};
 use uefi::table::boot::{BootServices, OpenProtocolAttributes, OpenProtocolParams};
-use uefi::Result;
+use uefi::{Result, Status};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Rect {
+    pub const fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.w && y - self.y < self.h
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.w).min(other.x + other.w);
+        let y1 = (self.y + self.h).min(other.y + other.h);
+        Rect::new(x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    /// Smallest rectangle covering both; empty rectangles are ignored.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.w).max(other.x + other.w);
+        let y1 = (self.y + self.h).max(other.y + other.h);
+        Rect::new(x0, y0, x1 - x0, y1 - y0)
+    }
+}
+
+/// Bounds for the `*_clipped` primitives, which discard anything outside it on
+/// top of the usual screen clipping. An empty clip draws nothing.
+pub type Clip = Rect;
 
 pub fn with_gop<F, R>(boot_services: &BootServices, mut f: F) -> Result<R>
 where
@@ -22,16 +105,85 @@ where
     Ok(f(&mut gop))
 }
 
+/// Switches to the largest mode, preferring ones with a linear framebuffer
+/// over BltOnly, and returns its resolution. `modes()` needs boot services.
+pub fn set_best_mode(gop: &mut GraphicsOutput, bs: &BootServices) -> Result<(usize, usize)> {
+    let best = gop.modes(bs).max_by_key(|mode| {
+        let info = mode.info();
+        let (w, h) = info.resolution();
+        (info.pixel_format() != PixelFormat::BltOnly, w * h)
+    });
+    let mode = best.ok_or(Status::NOT_FOUND)?;
+    gop.set_mode(&mode)?;
+    Ok(mode.info().resolution())
+}
+
+/// Switches to the first mode of exactly `w` x `h`, failing with `NOT_FOUND`
+/// if there is none.
+pub fn set_mode_by_resolution(
+    gop: &mut GraphicsOutput,
+    bs: &BootServices,
+    w: usize,
+    h: usize,
+) -> Result {
+    let mode = gop
+        .modes(bs)
+        .find(|mode| mode.info().resolution() == (w, h))
+        .ok_or(Status::NOT_FOUND)?;
+    gop.set_mode(&mode)
+}
+
+/// Whether the current mode has no linear framebuffer. Such modes can only be
+/// drawn through `GraphicsOutput::blt`, which every primitive here falls back to.
+pub fn is_blt_only(gop: &GraphicsOutput) -> bool {
+    gop.current_mode_info().pixel_format() == PixelFormat::BltOnly
+}
+
+/// Fills an already clipped rectangle through `blt`.
+fn blt_fill(gop: &mut GraphicsOutput, x: usize, y: usize, w: usize, h: usize, color: u32) {
+    let color = BltPixel::new((color >> 16) as u8, (color >> 8) as u8, color as u8);
+    let _ = gop.blt(BltOp::VideoFill {
+        color,
+        dest: (x, y),
+        dims: (w, h),
+    });
+}
+
+/// Views `0x00RRGGBB` pixels as `BltPixel`s; both are B, G, R, reserved in memory.
+pub(crate) fn as_blt_pixels(pixels: &[u32]) -> &[BltPixel] {
+    unsafe { core::slice::from_raw_parts(pixels.as_ptr() as *const BltPixel, pixels.len()) }
+}
+
+#[cfg(feature = "alloc")]
+fn as_blt_pixels_mut(pixels: &mut [u32]) -> &mut [BltPixel] {
+    unsafe { core::slice::from_raw_parts_mut(pixels.as_mut_ptr() as *mut BltPixel, pixels.len()) }
+}
+
 pub fn clear(gop: &mut GraphicsOutput, color: u32) {
     let (width, height) = gop.current_mode_info().resolution();
+    if is_blt_only(gop) {
+        blt_fill(gop, 0, 0, width, height, color);
+        return;
+    }
+    if height == 0 {
+        return;
+    }
     let stride = gop.current_mode_info().stride();
     let mut framebuffer = gop.frame_buffer();
-    for y in 0..height {
-        let row_off = y * stride * 4;
-        for x in 0..width {
-            let off = row_off + x * 4;
-            unsafe { framebuffer.write_value(off, color) };
-        }
+    let len = (height - 1) * stride + width;
+    fill_visible(span(&mut framebuffer, 0, len), width, height, stride, color);
+}
+
+/// Fills the `width` x `height` visible area of a surface whose rows are
+/// `stride` pixels apart, leaving any row padding untouched.
+fn fill_visible(pixels: &mut [u32], width: usize, height: usize, stride: usize, color: u32) {
+    // Without row padding the visible area is one contiguous run.
+    if stride == width {
+        pixels[..width * height].fill(color);
+        return;
+    }
+    for row in pixels.chunks_mut(stride).take(height) {
+        row[..width].fill(color);
     }
 }
 
@@ -40,6 +192,10 @@ pub fn draw_pixel(gop: &mut GraphicsOutput, x: usize, y: usize, color: u32) {
     if x >= width || y >= height {
         return;
     }
+    if is_blt_only(gop) {
+        blt_fill(gop, x, y, 1, 1, color);
+        return;
+    }
 
     let stride = gop.current_mode_info().stride();
     let mut framebuffer = gop.frame_buffer();
@@ -56,17 +212,601 @@ pub fn fill_rect(gop: &mut GraphicsOutput, x: usize, y: usize, w: usize, h: usiz
     let max_h = sh - y;
     let w = w.min(max_w);
     let h = h.min(max_h);
+    if is_blt_only(gop) {
+        blt_fill(gop, x, y, w, h, color);
+        return;
+    }
     let stride = gop.current_mode_info().stride();
     let mut fb = gop.frame_buffer();
     let start = y * stride + x;
     for row in 0..h {
-        let base = (start + row * stride) * 4;
-        for col in 0..w {
-            unsafe { fb.write_value(base + col * 4, color) };
+        span(&mut fb, start + row * stride, w).fill(color);
+    }
+}
+
+/// Outlines a rectangle with a border `thickness` pixels wide, drawn inside
+/// the bounds. A border too thick to leave a hole fills the whole rectangle.
+pub fn draw_rect(
+    gop: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    thickness: usize,
+    color: u32,
+) {
+    if w == 0 || h == 0 || thickness == 0 {
+        return;
+    }
+    if thickness >= w.div_ceil(2) || thickness >= h.div_ceil(2) {
+        fill_rect(gop, x, y, w, h, color);
+        return;
+    }
+    let t = thickness;
+    let (right, inner_y, inner_h) = (x.saturating_add(w - t), y.saturating_add(t), h - 2 * t);
+    fill_rect(gop, x, y, w, t, color);
+    fill_rect(gop, x, y.saturating_add(h - t), w, t, color);
+    fill_rect(gop, x, inner_y, t, inner_h, color);
+    fill_rect(gop, right, inner_y, t, inner_h, color);
+}
+
+/// Fills a rectangle shading from `top` on the first row to `bottom` on the
+/// last, clipped to the screen.
+pub fn fill_gradient_v(
+    gop: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    top: u32,
+    bottom: u32,
+) {
+    let (_, sh) = gop.current_mode_info().resolution();
+    for row in 0..h.min(sh.saturating_sub(y)) {
+        fill_span(gop, x, y + row, w, lerp_color(top, bottom, row, h));
+    }
+}
+
+/// Fills a rectangle shading from `left` on the first column to `right` on
+/// the last, clipped to the screen.
+pub fn fill_gradient_h(
+    gop: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    left: u32,
+    right: u32,
+) {
+    let (sw, _) = gop.current_mode_info().resolution();
+    for col in 0..w.min(sw.saturating_sub(x)) {
+        fill_rect(gop, x + col, y, 1, h, lerp_color(left, right, col, w));
+    }
+}
+
+/// Channel-wise blend from `a` (step 0) to `b` (step `steps - 1`).
+fn lerp_color(a: u32, b: u32, step: usize, steps: usize) -> u32 {
+    if steps <= 1 {
+        return a;
+    }
+    let (i, n) = (step as i64, steps as i64 - 1);
+    let mut out = 0;
+    for shift in [0, 8, 16] {
+        let from = ((a >> shift) & 0xff) as i64;
+        let to = ((b >> shift) & 0xff) as i64;
+        out |= ((from + (to - from) * i / n) as u32) << shift;
+    }
+    out
+}
+
+/// Fills a horizontal run of `len` pixels starting at (`x`, `y`), clipped to the screen.
+pub fn fill_span(gop: &mut GraphicsOutput, x: usize, y: usize, len: usize, color: u32) {
+    let (sw, sh) = gop.current_mode_info().resolution();
+    if x >= sw || y >= sh || len == 0 {
+        return;
+    }
+    let len = len.min(sw - x);
+    if is_blt_only(gop) {
+        blt_fill(gop, x, y, len, 1, color);
+        return;
+    }
+    let stride = gop.current_mode_info().stride();
+    let mut fb = gop.frame_buffer();
+    span(&mut fb, y * stride + x, len).fill(color);
+}
+
+pub fn draw_hline(gop: &mut GraphicsOutput, x: usize, y: usize, w: usize, color: u32) {
+    fill_span(gop, x, y, w, color);
+}
+
+/// Copies a `w` x `h` block from (`src_x`, `src_y`) to (`dst_x`, `dst_y`),
+/// clipped so both stay on screen. Overlapping blocks copy correctly, so this
+/// can scroll or move a window without redrawing it.
+pub fn copy_rect(
+    gop: &mut GraphicsOutput,
+    src_x: usize,
+    src_y: usize,
+    dst_x: usize,
+    dst_y: usize,
+    w: usize,
+    h: usize,
+) {
+    let (sw, sh) = gop.current_mode_info().resolution();
+    if src_x.max(dst_x) >= sw || src_y.max(dst_y) >= sh {
+        return;
+    }
+    let w = w.min(sw - src_x).min(sw - dst_x);
+    let h = h.min(sh - src_y).min(sh - dst_y);
+    if w == 0 || h == 0 {
+        return;
+    }
+    if is_blt_only(gop) {
+        let _ = gop.blt(BltOp::VideoToVideo {
+            src: (src_x, src_y),
+            dest: (dst_x, dst_y),
+            dims: (w, h),
+        });
+        return;
+    }
+    let stride = gop.current_mode_info().stride();
+    let mut fb = gop.frame_buffer();
+    let base = fb.as_mut_ptr() as *mut u32;
+    let copy_row = |row: usize| unsafe {
+        let from = base.add((src_y + row) * stride + src_x);
+        let to = base.add((dst_y + row) * stride + dst_x);
+        core::ptr::copy(from, to, w);
+    };
+    // Walk away from the destination so no row is overwritten before it is read.
+    if dst_y <= src_y {
+        (0..h).for_each(copy_row);
+    } else {
+        (0..h).rev().for_each(copy_row);
+    }
+}
+
+/// Views `len` pixels starting at pixel index `start` as a `u32` slice.
+/// Callers must have clipped the run to the visible mode.
+pub(crate) fn span<'a>(fb: &'a mut FrameBuffer, start: usize, len: usize) -> &'a mut [u32] {
+    debug_assert!((start + len) * 4 <= fb.size());
+    unsafe { core::slice::from_raw_parts_mut((fb.as_mut_ptr() as *mut u32).add(start), len) }
+}
+
+/// Bresenham line between both endpoints, inclusive. The segment is clipped
+/// to the screen first, so only visible pixels are stepped and a line entirely
+/// off-screen is a no-op.
+pub fn draw_line(gop: &mut GraphicsOutput, x0: i32, y0: i32, x1: i32, y1: i32, color: u32) {
+    let (w, h) = gop.current_mode_info().resolution();
+    let Some((x0, y0, x1, y1)) = clip_line(
+        (x0 as i64, y0 as i64),
+        (x1 as i64, y1 as i64),
+        w as i64 - 1,
+        h as i64 - 1,
+    ) else {
+        return;
+    };
+    if y0 == y1 {
+        let len = (x1 - x0).unsigned_abs() as usize + 1;
+        fill_span(gop, x0.min(x1) as usize, y0 as usize, len, color);
+        return;
+    }
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    let (mut x, mut y) = (x0, y0);
+    loop {
+        draw_pixel(gop, x as usize, y as usize, color);
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+const OUT_LEFT: u8 = 1;
+const OUT_RIGHT: u8 = 2;
+const OUT_TOP: u8 = 4;
+const OUT_BOTTOM: u8 = 8;
+
+fn outcode(x: i64, y: i64, max_x: i64, max_y: i64) -> u8 {
+    let mut code = 0;
+    if x < 0 {
+        code |= OUT_LEFT;
+    } else if x > max_x {
+        code |= OUT_RIGHT;
+    }
+    if y < 0 {
+        code |= OUT_TOP;
+    } else if y > max_y {
+        code |= OUT_BOTTOM;
+    }
+    code
+}
+
+/// `a0 + (a1 - a0) * num / den`, widened so full-range `i32` endpoints can't overflow.
+fn lerp_coord(a0: i64, a1: i64, num: i64, den: i64) -> i64 {
+    a0 + ((a1 - a0) as i128 * num as i128 / den as i128) as i64
+}
+
+/// Cohen-Sutherland clip of the segment `p0`-`p1` to `0..=max_x` by
+/// `0..=max_y`. Returns the visible part, or `None` if none of it is.
+fn clip_line(
+    p0: (i64, i64),
+    p1: (i64, i64),
+    max_x: i64,
+    max_y: i64,
+) -> Option<(i64, i64, i64, i64)> {
+    if max_x < 0 || max_y < 0 {
+        return None;
+    }
+    let ((mut x0, mut y0), (mut x1, mut y1)) = (p0, p1);
+    let mut c0 = outcode(x0, y0, max_x, max_y);
+    let mut c1 = outcode(x1, y1, max_x, max_y);
+    loop {
+        if c0 | c1 == 0 {
+            return Some((x0, y0, x1, y1));
+        }
+        if c0 & c1 != 0 {
+            return None;
+        }
+        // Move whichever endpoint is outside onto the edge it crosses.
+        let code = if c0 != 0 { c0 } else { c1 };
+        let (x, y) = if code & OUT_TOP != 0 {
+            (lerp_coord(x0, x1, -y0, y1 - y0), 0)
+        } else if code & OUT_BOTTOM != 0 {
+            (lerp_coord(x0, x1, max_y - y0, y1 - y0), max_y)
+        } else if code & OUT_LEFT != 0 {
+            (0, lerp_coord(y0, y1, -x0, x1 - x0))
+        } else {
+            (max_x, lerp_coord(y0, y1, max_x - x0, x1 - x0))
+        };
+        if code == c0 {
+            (x0, y0) = (x, y);
+            c0 = outcode(x0, y0, max_x, max_y);
+        } else {
+            (x1, y1) = (x, y);
+            c1 = outcode(x1, y1, max_x, max_y);
         }
     }
 }
 
+/// Midpoint circle outline. Parts off-screen are clipped.
+pub fn draw_circle(gop: &mut GraphicsOutput, cx: usize, cy: usize, r: usize, color: u32) {
+    let (cx, cy) = (cx as isize, cy as isize);
+    let plot = |gop: &mut GraphicsOutput, x: isize, y: isize| {
+        if x >= 0 && y >= 0 {
+            draw_pixel(gop, x as usize, y as usize, color);
+        }
+    };
+    let (mut x, mut y, mut d) = (r as isize, 0isize, 1 - r as isize);
+    while x >= y {
+        // Mirror the octant point into all eight octants.
+        for (a, b) in [(x, y), (y, x)] {
+            for (sx, sy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                plot(gop, cx + sx * a, cy + sy * b);
+            }
+        }
+        y += 1;
+        if d < 0 {
+            d += 2 * y + 1;
+        } else {
+            x -= 1;
+            d += 2 * (y - x) + 1;
+        }
+    }
+}
+
+/// Filled circle drawn as one horizontal span per scanline, clipped to the screen.
+pub fn fill_circle(gop: &mut GraphicsOutput, cx: usize, cy: usize, r: usize, color: u32) {
+    let (cx, cy) = (cx as isize, cy as isize);
+    let span = |gop: &mut GraphicsOutput, half: isize, y: isize| {
+        let (l, r) = ((cx - half).max(0), cx + half);
+        if y >= 0 && r >= l {
+            fill_span(gop, l as usize, y as usize, (r - l + 1) as usize, color);
+        }
+    };
+    let (mut x, mut y, mut d) = (r as isize, 0isize, 1 - r as isize);
+    while x >= y {
+        span(gop, x, cy + y);
+        span(gop, x, cy - y);
+        span(gop, y, cy + x);
+        span(gop, y, cy - x);
+        y += 1;
+        if d < 0 {
+            d += 2 * y + 1;
+        } else {
+            x -= 1;
+            d += 2 * (y - x) + 1;
+        }
+    }
+}
+
+pub fn draw_text(gop: &mut GraphicsOutput, x: usize, y: usize, text: &str, color: u32) {
+    draw_text_with(gop, x, y, text, color, &font::DEFAULT);
+}
+
+pub fn draw_text_with(
+    gop: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    text: &str,
+    color: u32,
+    font: &dyn Font,
+) {
+    let mut cx = x;
+    for ch in text.chars() {
+        font.draw_glyph(gop, cx, y, ch, color);
+        cx += font.advance();
+    }
+}
+
+pub fn draw_pixel_clipped(gop: &mut GraphicsOutput, clip: &Clip, x: usize, y: usize, color: u32) {
+    if clip.contains(x, y) {
+        draw_pixel(gop, x, y, color);
+    }
+}
+
+pub fn fill_rect_clipped(
+    gop: &mut GraphicsOutput,
+    clip: &Clip,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    color: u32,
+) {
+    let r = Rect::new(x, y, w, h).intersect(clip);
+    if !r.is_empty() {
+        fill_rect(gop, r.x, r.y, r.w, r.h, color);
+    }
+}
+
+/// Like [`draw_text_with`], but glyphs crossing the clip edge are cut off.
+/// Fonts without bitmaps can't be cut, so their glyphs are drawn only when
+/// the whole cell fits.
+pub fn draw_text_clipped(
+    gop: &mut GraphicsOutput,
+    clip: &Clip,
+    x: usize,
+    y: usize,
+    text: &str,
+    color: u32,
+    font: &dyn Font,
+) {
+    let mut cx = x;
+    for ch in text.chars() {
+        let cell = Rect::new(cx, y, font.advance(), font.height());
+        if cell.intersect(clip) == cell {
+            font.draw_glyph(gop, cx, y, ch, color);
+        } else {
+            for (gy, bits) in font.glyph(ch).iter().enumerate() {
+                for gx in 0..8 {
+                    if bits & (0x80 >> gx) != 0 {
+                        draw_pixel_clipped(gop, clip, cx + gx, y + gy, color);
+                    }
+                }
+            }
+        }
+        cx += font.advance();
+    }
+}
+
+pub fn blit(gop: &mut GraphicsOutput, x: usize, y: usize, w: usize, h: usize, pixels: &[u32]) {
+    let (sw, sh) = gop.current_mode_info().resolution();
+    if x >= sw || y >= sh || w == 0 || h == 0 || pixels.len() < w * h {
+        return;
+    }
+    let cw = w.min(sw - x);
+    let ch = h.min(sh - y);
+    if is_blt_only(gop) {
+        let _ = gop.blt(BltOp::BufferToVideo {
+            buffer: as_blt_pixels(pixels),
+            src: BltRegion::SubRectangle {
+                coords: (0, 0),
+                px_stride: w,
+            },
+            dest: (x, y),
+            dims: (cw, ch),
+        });
+        return;
+    }
+    let stride = gop.current_mode_info().stride();
+    let mut fb = gop.frame_buffer();
+    for row in 0..ch {
+        let base = ((y + row) * stride + x) * 4;
+        let src = &pixels[row * w..row * w + cw];
+        for (col, &px) in src.iter().enumerate() {
+            unsafe { fb.write_value(base + col * 4, px) };
+        }
+    }
+}
+
+/// Sets every pixel of `rect` (clipped to the screen) to `f(x, y)`.
+///
+/// Rows are computed into a scratch buffer and written with [`blit`], so this
+/// works in BltOnly modes and touches video memory once per row.
+#[cfg(feature = "alloc")]
+pub fn for_each_pixel<F>(gop: &mut GraphicsOutput, rect: Rect, mut f: F)
+where
+    F: FnMut(usize, usize) -> u32,
+{
+    let (sw, sh) = gop.current_mode_info().resolution();
+    let rect = rect.intersect(&Rect::new(0, 0, sw, sh));
+    if rect.is_empty() {
+        return;
+    }
+    let mut row = vec![0u32; rect.w];
+    for y in rect.y..rect.y + rect.h {
+        for (i, px) in row.iter_mut().enumerate() {
+            *px = f(rect.x + i, y);
+        }
+        blit(gop, rect.x, y, rect.w, 1, &row);
+    }
+}
+
+/// Copies the `w` x `h` block at (`x`, `y`) into a new buffer, row by row.
+/// Pixels off-screen read as 0. Put it back with [`restore_region`].
+#[cfg(feature = "alloc")]
+pub fn save_region(gop: &mut GraphicsOutput, x: usize, y: usize, w: usize, h: usize) -> Vec<u32> {
+    let mut saved = vec![0u32; w * h];
+    let (sw, sh) = gop.current_mode_info().resolution();
+    if x >= sw || y >= sh {
+        return saved;
+    }
+    let (cw, ch) = (w.min(sw - x), h.min(sh - y));
+    if is_blt_only(gop) {
+        let _ = gop.blt(BltOp::VideoToBltBuffer {
+            buffer: as_blt_pixels_mut(&mut saved),
+            src: (x, y),
+            dest: BltRegion::SubRectangle {
+                coords: (0, 0),
+                px_stride: w,
+            },
+            dims: (cw, ch),
+        });
+        return saved;
+    }
+    let stride = gop.current_mode_info().stride();
+    let mut fb = gop.frame_buffer();
+    for row in 0..ch {
+        let src = span(&mut fb, (y + row) * stride + x, cw);
+        saved[row * w..row * w + cw].copy_from_slice(src);
+    }
+    saved
+}
+
+/// Writes back a block taken by [`save_region`] with the same `w` and `h`.
+/// A buffer too short for `w * h` is ignored.
+pub fn restore_region(
+    gop: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    pixels: &[u32],
+) {
+    blit(gop, x, y, w, h, pixels);
+}
+
+/// Reads the pixel at (`x`, `y`) as `0x00RRGGBB`.
+///
+/// Fails with `UNSUPPORTED` in BltOnly modes, which have no framebuffer to
+/// read, and `INVALID_PARAMETER` off-screen.
+pub fn get_pixel(gop: &mut GraphicsOutput, x: usize, y: usize) -> Result<u32> {
+    if is_blt_only(gop) {
+        return Err(Status::UNSUPPORTED.into());
+    }
+    read_pixel(gop, x, y).ok_or(Status::INVALID_PARAMETER.into())
+}
+
+/// Reads the pixel at (`x`, `y`) in the mode's native layout, or `None`
+/// off-screen and in BltOnly modes.
+pub fn read_pixel(gop: &mut GraphicsOutput, x: usize, y: usize) -> Option<u32> {
+    let (width, height) = gop.current_mode_info().resolution();
+    if x >= width || y >= height || is_blt_only(gop) {
+        return None;
+    }
+    let stride = gop.current_mode_info().stride();
+    let fb = gop.frame_buffer();
+    Some(unsafe { fb.read_value::<u32>((y * stride + x) * 4) } & 0x00ff_ffff)
+}
+
+/// Draws `color` over the pixel at (`x`, `y`) with opacity `alpha` (255 is
+/// opaque). A no-op where [`read_pixel`] can't read.
+pub fn blend_pixel(gop: &mut GraphicsOutput, x: usize, y: usize, color: u32, alpha: u8) {
+    let Some(dst) = read_pixel(gop, x, y) else {
+        return;
+    };
+    let (a, inv) = (alpha as u32, 255 - alpha as u32);
+    let mut out = 0;
+    for shift in [0, 8, 16] {
+        let s = (color >> shift) & 0xff;
+        let d = (dst >> shift) & 0xff;
+        out |= ((s * a + d * inv) / 255) << shift;
+    }
+    draw_pixel(gop, x, y, out);
+}
+
+#[cfg(feature = "alloc")]
+pub fn flood_fill(gop: &mut GraphicsOutput, x: usize, y: usize, color: u32) -> Result {
+    let (w, h) = gop.current_mode_info().resolution();
+    flood_fill_bounded(gop, x, y, color, Rect::new(0, 0, w, h))
+}
+
+/// Scanline flood fill that never touches pixels outside `bounds`.
+///
+/// Needs to read the framebuffer, so it fails with `UNSUPPORTED` in BltOnly modes.
+#[cfg(feature = "alloc")]
+pub fn flood_fill_bounded(
+    gop: &mut GraphicsOutput,
+    x: usize,
+    y: usize,
+    color: u32,
+    bounds: Rect,
+) -> Result {
+    if is_blt_only(gop) {
+        return Err(Status::UNSUPPORTED.into());
+    }
+    let (sw, sh) = gop.current_mode_info().resolution();
+    let bounds = bounds.intersect(&Rect::new(0, 0, sw, sh));
+    if !bounds.contains(x, y) {
+        return Ok(());
+    }
+    let target = match read_pixel(gop, x, y) {
+        Some(c) if c != color & 0x00ff_ffff => c,
+        _ => return Ok(()),
+    };
+
+    let stride = gop.current_mode_info().stride();
+    let mut fb = gop.frame_buffer();
+    let at = |x: usize, y: usize| (y * stride + x) * 4;
+    let matches = |fb: &FrameBuffer, x: usize, y: usize| {
+        let px: u32 = unsafe { fb.read_value(at(x, y)) };
+        px & 0x00ff_ffff == target
+    };
+    let x_end = bounds.x + bounds.w;
+    let y_end = bounds.y + bounds.h;
+
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    stack.push((x, y));
+    while let Some((sx, sy)) = stack.pop() {
+        if !matches(&fb, sx, sy) {
+            continue;
+        }
+        let mut left = sx;
+        while left > bounds.x && matches(&fb, left - 1, sy) {
+            left -= 1;
+        }
+        let mut above = false;
+        let mut below = false;
+        let mut px = left;
+        while px < x_end && matches(&fb, px, sy) {
+            unsafe { fb.write_value(at(px, sy), color) };
+            for (ny, seeded) in [(sy.checked_sub(1), &mut above), (Some(sy + 1), &mut below)] {
+                let Some(ny) = ny.filter(|&ny| ny >= bounds.y && ny < y_end) else {
+                    continue;
+                };
+                let hit = matches(&fb, px, ny);
+                if hit && !*seeded {
+                    stack.push((px, ny));
+                }
+                *seeded = hit;
+            }
+            px += 1;
+        }
+    }
+    Ok(())
+}
+
 pub fn width(gop: &GraphicsOutput) -> usize {
     gop.current_mode_info().resolution().0
 }
@@ -75,6 +815,112 @@ pub fn height(gop: &GraphicsOutput) -> usize {
     gop.current_mode_info().resolution().1
 }
 
+/// Packs a color as `0x00RRGGBB`, the layout of BGR framebuffers and `blt`.
+/// Use [`to_color_for`] when the mode might store pixels differently.
 pub fn to_color(r: u8, g: u8, b: u8) -> u32 {
     ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
 }
+
+pub fn pixel_format(gop: &GraphicsOutput) -> PixelFormat {
+    gop.current_mode_info().pixel_format()
+}
+
+/// Packs a color in the active mode's pixel layout.
+///
+/// RGB modes swap the red and blue bytes, and bitmask modes place each
+/// channel under its reported mask. Anything else, including a bitmask mode
+/// that reports no masks, falls back to BGR; on such a mode colors may come
+/// out wrong.
+pub fn to_color_for(gop: &GraphicsOutput, r: u8, g: u8, b: u8) -> u32 {
+    let mode = gop.current_mode_info();
+    pack_color(mode.pixel_format(), mode.pixel_bitmask(), r, g, b)
+}
+
+pub(crate) fn pack_color(
+    format: PixelFormat,
+    mask: Option<PixelBitmask>,
+    r: u8,
+    g: u8,
+    b: u8,
+) -> u32 {
+    match (format, mask) {
+        (PixelFormat::Rgb, _) => (b as u32) << 16 | (g as u32) << 8 | r as u32,
+        (PixelFormat::Bitmask, Some(mask)) => {
+            place(r, mask.red) | place(g, mask.green) | place(b, mask.blue)
+        }
+        _ => to_color(r, g, b),
+    }
+}
+
+/// Scales an 8-bit channel into the bits selected by `mask`.
+fn place(value: u8, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+    let bits = mask.count_ones().min(8);
+    ((value as u32) >> (8 - bits)) << mask.trailing_zeros() & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_visible_covers_unpadded_surface() {
+        let mut pixels = [0u32; 12];
+        fill_visible(&mut pixels, 4, 3, 4, 0xabcdef);
+        assert!(pixels.iter().all(|&p| p == 0xabcdef));
+    }
+
+    #[test]
+    fn fill_visible_skips_row_padding() {
+        let (width, height, stride) = (3, 4, 5);
+        let mut pixels = [7u32; 20];
+        fill_visible(&mut pixels, width, height, stride, 1);
+        for (i, &p) in pixels.iter().enumerate() {
+            let expected = if i % stride < width { 1 } else { 7 };
+            assert_eq!(p, expected, "pixel {}", i);
+        }
+    }
+
+    #[test]
+    fn fill_visible_accepts_a_short_last_row() {
+        let mut pixels = [0u32; 7];
+        fill_visible(&mut pixels, 2, 2, 5, 9);
+        assert_eq!(pixels, [9, 9, 0, 0, 0, 9, 9]);
+    }
+
+    #[test]
+    fn inside_line_is_unchanged() {
+        assert_eq!(clip_line((1, 2), (30, 40), 99, 99), Some((1, 2, 30, 40)));
+    }
+
+    #[test]
+    fn off_screen_line_is_rejected() {
+        assert_eq!(clip_line((-5, -5), (-1, 50), 99, 99), None);
+        assert_eq!(clip_line((100, 0), (200, 99), 99, 99), None);
+        assert_eq!(clip_line((0, 0), (5, 5), -1, 99), None);
+    }
+
+    #[test]
+    fn horizontal_line_clips_to_both_edges() {
+        assert_eq!(
+            clip_line((i32::MIN as i64, 5), (i32::MAX as i64, 5), 99, 49),
+            Some((0, 5, 99, 5))
+        );
+    }
+
+    #[test]
+    fn diagonal_line_clips_to_corner() {
+        assert_eq!(clip_line((-10, -10), (200, 200), 99, 99), Some((0, 0, 99, 99)));
+        assert_eq!(clip_line((-1, 100), (100, -1), 99, 99), Some((0, 99, 99, 0)));
+    }
+
+    #[test]
+    fn extreme_endpoints_do_not_overflow() {
+        let m = i32::MIN as i64;
+        let x = i32::MAX as i64;
+        assert_eq!(clip_line((m, m), (x, x), 99, 99), Some((0, 0, 99, 99)));
+        assert_eq!(clip_line((m, x), (x, m), 99, 99), None);
+    }
+}
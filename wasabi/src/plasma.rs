@@ -0,0 +1,25 @@
+use uefi::proto::console::gop::GraphicsOutput;
+
+use crate::fixed::sin;
+use crate::{for_each_pixel, pack_color, Rect};
+
+/// Fills `rect` with a sine-sum plasma. Advance `t` each frame to animate it;
+/// the pattern is anchored to `rect`'s origin, so moving the rect moves it too.
+pub fn fill_plasma(gop: &mut GraphicsOutput, rect: Rect, t: u32) {
+    // Only `t mod 256` matters to the 256-step sine; reducing it keeps the sums small.
+    let t = t & 0xff;
+    let mode = gop.current_mode_info();
+    let (format, mask) = (mode.pixel_format(), mode.pixel_bitmask());
+    for_each_pixel(gop, rect, |x, y| {
+        let (x, y) = ((x - rect.x) as u32, (y - rect.y) as u32);
+        let wave = |v: u32| sin(v as u8) as i32;
+        let v = wave(x * 3 + t)
+            + wave(y * 2 + t * 2)
+            + wave((x + y) * 2 + t * 3)
+            + wave(x * y / 64 + t);
+        // -508..=508 onto one turn of the palette.
+        let idx = ((v + 508) * 255 / 1016) as u8;
+        let channel = |phase: u8| (sin(idx.wrapping_add(phase)) as i32 + 128) as u8;
+        pack_color(format, mask, channel(0), channel(85), channel(170))
+    });
+}
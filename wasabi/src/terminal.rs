@@ -0,0 +1,320 @@
+//! A character terminal rendered into a [`Display`] back buffer.
+//!
+//! Text goes in through [`core::fmt::Write`]. `\n` starts a new line (the
+//! firmware console's CRLF behavior), `\r`, `\t` and backspace move the
+//! cursor, and `ESC [ ... m` sets colors from the 16-color ANSI palette with
+//! bold brightening 30-37. Other escape sequences are swallowed. Nothing
+//! reaches the screen until [`Terminal::flush`], which copies only the cells
+//! that changed.
+//!
+//! Only bitmap fonts are supported; a font without bitmaps renders blanks.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use shared::color::Rgb;
+use uefi::proto::console::gop::GraphicsOutput;
+
+use crate::display::{Display, ToGopNative};
+use crate::font::Font;
+use crate::Rect;
+
+const TAB_WIDTH: usize = 8;
+const MAX_PARAMS: usize = 4;
+/// Height of the underline cursor in pixels.
+const CURSOR_H: usize = 2;
+
+/// SGR colors 30-37 then 90-97, in ANSI order rather than VGA order.
+const ANSI_PALETTE: [Rgb; 16] = [
+    Rgb::BLACK,
+    Rgb::RED,
+    Rgb::GREEN,
+    Rgb::BROWN,
+    Rgb::BLUE,
+    Rgb::MAGENTA,
+    Rgb::CYAN,
+    Rgb::LIGHT_GRAY,
+    Rgb::DARK_GRAY,
+    Rgb::LIGHT_RED,
+    Rgb::LIGHT_GREEN,
+    Rgb::YELLOW,
+    Rgb::LIGHT_BLUE,
+    Rgb::LIGHT_MAGENTA,
+    Rgb::LIGHT_CYAN,
+    Rgb::WHITE,
+];
+
+#[derive(Clone, Copy)]
+struct Cell {
+    c: char,
+    fg: u32,
+    bg: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// Saw `ESC`.
+    Start,
+    /// Inside `ESC [`, collecting parameters.
+    Csi,
+}
+
+pub struct Terminal {
+    display: Display,
+    font: &'static dyn Font,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Cell>,
+    col: usize,
+    row: usize,
+    fg: u32,
+    bg: u32,
+    default_fg: u32,
+    default_bg: u32,
+    bold: bool,
+    palette: [u32; 16],
+    cursor_visible: bool,
+    escape: Escape,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+}
+
+impl Terminal {
+    /// A terminal covering the whole screen in `font`, light gray on black.
+    pub fn new(gop: &GraphicsOutput, font: &'static dyn Font) -> Self {
+        let display = Display::new(gop);
+        let info = *display.info();
+        let cols = (info.width / font.advance()).max(1);
+        let rows = (info.height / font.height()).max(1);
+        let palette = ANSI_PALETTE.map(|c| c.to_gop_native(&info));
+        let (fg, bg) = (palette[7], palette[0]);
+        let mut term = Self {
+            display,
+            font,
+            cols,
+            rows,
+            cells: vec![Cell { c: ' ', fg, bg }; cols * rows],
+            col: 0,
+            row: 0,
+            fg,
+            bg,
+            default_fg: fg,
+            default_bg: bg,
+            bold: false,
+            palette,
+            cursor_visible: true,
+            escape: Escape::None,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+        };
+        term.clear();
+        term
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The cursor as (column, row).
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.col, self.row)
+    }
+
+    /// Moves the cursor, clamped to the grid.
+    pub fn set_cursor(&mut self, col: usize, row: usize) {
+        self.hide_cursor();
+        self.col = col.min(self.cols.saturating_sub(1));
+        self.row = row.min(self.rows.saturating_sub(1));
+        self.show_cursor();
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.hide_cursor();
+        self.cursor_visible = visible;
+        self.show_cursor();
+    }
+
+    /// Colors for text written from now on.
+    pub fn set_colors(&mut self, fg: Rgb, bg: Rgb) {
+        let info = *self.display.info();
+        self.fg = fg.to_gop_native(&info);
+        self.bg = bg.to_gop_native(&info);
+    }
+
+    pub fn reset_colors(&mut self) {
+        self.fg = self.default_fg;
+        self.bg = self.default_bg;
+        self.bold = false;
+    }
+
+    /// Blanks the grid in the current background and homes the cursor.
+    pub fn clear(&mut self) {
+        let blank = self.blank();
+        self.cells.fill(blank);
+        self.display.clear(self.bg);
+        self.col = 0;
+        self.row = 0;
+        self.show_cursor();
+    }
+
+    /// Copies changed cells to the screen.
+    pub fn flush(&mut self, gop: &mut GraphicsOutput) {
+        self.display.present(gop);
+    }
+
+    /// Copies every cell to the screen, e.g. after something else drew over it.
+    pub fn repaint(&mut self, gop: &mut GraphicsOutput) {
+        self.display.back_mut().present_all(gop);
+    }
+
+    fn blank(&self) -> Cell {
+        Cell {
+            c: ' ',
+            fg: self.fg,
+            bg: self.bg,
+        }
+    }
+
+    fn feed(&mut self, c: char) {
+        match self.escape {
+            Escape::Start => {
+                self.escape = if c == '[' { Escape::Csi } else { Escape::None };
+                self.params = [0; MAX_PARAMS];
+                self.param_count = 0;
+            }
+            Escape::Csi => self.feed_csi(c),
+            Escape::None => match c {
+                '\u{1b}' => self.escape = Escape::Start,
+                '\n' => self.newline(),
+                '\r' => self.col = 0,
+                '\t' => {
+                    let stop = (self.col / TAB_WIDTH + 1) * TAB_WIDTH;
+                    while self.col < stop.min(self.cols) {
+                        self.put(' ');
+                    }
+                }
+                '\u{8}' => self.col = self.col.saturating_sub(1),
+                c if c.is_control() => {}
+                c => self.put(c),
+            },
+        }
+    }
+
+    fn feed_csi(&mut self, c: char) {
+        match c {
+            '0'..='9' => {
+                if self.param_count == 0 {
+                    self.param_count = 1;
+                }
+                let p = &mut self.params[self.param_count - 1];
+                *p = p.saturating_mul(10).saturating_add(c as u16 - '0' as u16);
+            }
+            ';' => self.param_count = (self.param_count.max(1) + 1).min(MAX_PARAMS),
+            '@'..='~' => {
+                if c == 'm' {
+                    self.sgr();
+                }
+                self.escape = Escape::None;
+            }
+            _ => self.escape = Escape::None,
+        }
+    }
+
+    fn sgr(&mut self) {
+        // `ESC[m` is the same as `ESC[0m`.
+        let count = self.param_count.max(1);
+        for i in 0..count {
+            match self.params[i] {
+                0 => self.reset_colors(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                p @ 30..=37 => {
+                    let bright = if self.bold { 8 } else { 0 };
+                    self.fg = self.palette[(p - 30) as usize + bright];
+                }
+                39 => self.fg = self.default_fg,
+                p @ 40..=47 => self.bg = self.palette[(p - 40) as usize],
+                49 => self.bg = self.default_bg,
+                p @ 90..=97 => self.fg = self.palette[(p - 90) as usize + 8],
+                p @ 100..=107 => self.bg = self.palette[(p - 100) as usize + 8],
+                _ => {}
+            }
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        if self.col >= self.cols {
+            self.newline();
+        }
+        let (fg, bg) = (self.fg, self.bg);
+        self.cells[self.row * self.cols + self.col] = Cell { c, fg, bg };
+        self.draw_cell(self.col, self.row);
+        self.col += 1;
+    }
+
+    fn newline(&mut self) {
+        self.col = 0;
+        if self.row + 1 < self.rows {
+            self.row += 1;
+        } else {
+            self.scroll();
+        }
+    }
+
+    /// Moves every row up by one and blanks the last.
+    fn scroll(&mut self) {
+        let (cw, ch) = (self.font.advance(), self.font.height());
+        let width = self.cols * cw;
+        self.display
+            .copy_rect(Rect::new(0, ch, width, (self.rows - 1) * ch), 0, 0);
+        self.cells.copy_within(self.cols.., 0);
+        let blank = self.blank();
+        let last = (self.rows - 1) * self.cols;
+        self.cells[last..].fill(blank);
+        self.display
+            .fill_rect(0, (self.rows - 1) * ch, width, ch, blank.bg);
+    }
+
+    fn draw_cell(&mut self, col: usize, row: usize) {
+        let Some(cell) = self.cells.get(row * self.cols + col).copied() else {
+            return;
+        };
+        let (cw, ch) = (self.font.advance(), self.font.height());
+        self.display
+            .draw_char(col * cw, row * ch, cell.c, self.font, cell.fg, cell.bg);
+    }
+
+    /// Erases the cursor by redrawing the cell under it.
+    fn hide_cursor(&mut self) {
+        if self.cursor_visible && self.col < self.cols {
+            self.draw_cell(self.col, self.row);
+        }
+    }
+
+    fn show_cursor(&mut self) {
+        if !self.cursor_visible || self.col >= self.cols {
+            return;
+        }
+        let (cw, ch) = (self.font.advance(), self.font.height());
+        let fg = self.cells[self.row * self.cols + self.col].fg;
+        let y = (self.row + 1) * ch - CURSOR_H.min(ch);
+        self.display
+            .fill_rect(self.col * cw, y, cw, CURSOR_H.min(ch), fg);
+    }
+}
+
+impl fmt::Write for Terminal {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.hide_cursor();
+        for c in s.chars() {
+            self.feed(c);
+        }
+        self.show_cursor();
+        Ok(())
+    }
+}
@@ -4,40 +4,89 @@
 
 extern crate alloc;
 
+mod menu;
+
+use core::ffi::c_void;
+use core::fmt::Write as _;
 use core::panic::PanicInfo;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, Ordering};
 use linked_list_allocator::LockedHeap;
+use menu::Action;
+use nori::FsBackend;
+use shared::config::{self, Config};
+use shared::heap::HeapStats;
+use shared::log::Log;
 use shared::store::FIRMWARE_NAME;
-use shared::{kprintln, vga};
+use shared::{backtrace, kprintln, vga};
 use uefi::prelude::*;
-use uefi::table::boot::MemoryType;
+use uefi::proto::console::text::{Key, ScanCode};
+use uefi::table::boot::{AllocateType, EventType, MemoryType, Tpl};
+use uefi::table::runtime::ResetType;
+use uefi::Event;
 
 #[global_allocator]
 static ALLOCATOR: LockedHeap = LockedHeap::empty();
 
+/// Set once `uefi::helpers::init` has run and cleared when boot services
+/// exit, so the panic handler only touches the firmware console while it's there.
+static BOOT_CONSOLE: AtomicBool = AtomicBool::new(false);
+
 #[entry]
 fn main(handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
+    shared::serial::init();
     kprintln!(
         &mut system_table,
         ">>> {FIRMWARE_NAME} Stage 0 - Firmware initialization <<<"
     );
 
     uefi::helpers::init(&mut system_table).unwrap();
+    // Without the exit notification we couldn't tell when the console goes
+    // away, so the panic handler sticks to VGA in that case.
+    let watching = unsafe {
+        system_table.boot_services().create_event(
+            EventType::SIGNAL_EXIT_BOOT_SERVICES,
+            Tpl::NOTIFY,
+            Some(boot_services_exited),
+            None,
+        )
+    };
+    BOOT_CONSOLE.store(watching.is_ok(), Ordering::Release);
 
-    let heap_size = 1024 * 1024;
+    let config = read_config(system_table.boot_services());
+    let heap_size = heap_size(system_table.boot_services(), &config).next_multiple_of(PAGE_SIZE);
+    // Whole pages keep a large heap out of the firmware's pool and page aligned.
     let heap_start = system_table
         .boot_services()
-        .allocate_pool(MemoryType::LOADER_DATA, heap_size)
-        .unwrap();
+        .allocate_pages(
+            AllocateType::AnyPages,
+            MemoryType::LOADER_DATA,
+            heap_size / PAGE_SIZE,
+        )
+        .unwrap() as *mut u8;
     unsafe {
         ALLOCATOR.lock().init(heap_start, heap_size);
     }
+    shared::heap::set_reporter(|| {
+        let heap = ALLOCATOR.lock();
+        HeapStats {
+            used: heap.used(),
+            free: heap.free(),
+        }
+    });
 
     kprintln!(&mut system_table, "Welcome to Zap!");
 
-    let _ = core::fmt::Write::write_str(&mut system_table.stdout(), "Booting in ");
-    for i in (1..=3).rev() {
-        let _ = core::fmt::Write::write_fmt(&mut system_table.stdout(), format_args!("{i}... "));
-        system_table.boot_services().stall(1_000_000);
+    let entries = menu::load(&mut system_table);
+    // Escape during the countdown opens the boot menu; other keys boot now.
+    let action = match countdown(&mut system_table, "Booting in ", config.countdown) {
+        Some(Key::Special(ScanCode::ESCAPE)) => menu::choose(&mut system_table, &entries),
+        _ => entries[0].action,
+    };
+    if action == Action::Firmware {
+        // Returning from the entry point hands control back to the firmware
+        // boot manager, which offers its shell or the next boot option.
+        return Status::SUCCESS;
     }
     let _ = system_table.stdout().clear();
     kprintln!(&mut system_table, "Booting...");
@@ -48,37 +97,130 @@ fn main(handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
     Status::SUCCESS
 }
 
+/// Polls for a key this often while counting down.
+const COUNTDOWN_SLICE_US: usize = 100_000;
+
+/// Seconds the panic screen stays up before rebooting.
+const PANIC_REBOOT_SECS: u8 = 10;
+
+/// Prints `label` then `3... 2... 1...` over `secs` seconds. Any key cuts it
+/// short and is returned.
+fn countdown(st: &mut SystemTable<Boot>, label: &str, secs: u8) -> Option<Key> {
+    if secs == 0 {
+        return None;
+    }
+    let _ = st.stdout().write_str(label);
+    for i in (1..=secs).rev() {
+        let _ = write!(st.stdout(), "{i}... ");
+        for _ in 0..1_000_000 / COUNTDOWN_SLICE_US {
+            if let Ok(Some(key)) = st.stdin().read_key() {
+                return Some(key);
+            }
+            st.boot_services().stall(COUNTDOWN_SLICE_US);
+        }
+    }
+    None
+}
+
+/// UEFI pages are always 4 KiB.
+const PAGE_SIZE: usize = 4096;
+
+/// Largest share of free conventional memory the heap may take.
+const HEAP_MAX_FRACTION: usize = 2;
+
+/// Reads `\qubit.conf` into a stack buffer, since the heap is sized from it.
+/// A missing or unreadable file gives the defaults.
+fn read_config(bt: &BootServices) -> Config {
+    let mut buf = [0u8; 1024];
+    let len = nori::UefiFs::new(bt)
+        .and_then(|fs| fs.read(config::PATH, 0, &mut buf))
+        .unwrap_or(0);
+    Config::parse(core::str::from_utf8(&buf[..len]).unwrap_or(""))
+}
+
+/// The configured heap size, clamped to half of the free conventional memory
+/// when the firmware reports less than that.
+fn heap_size(bt: &BootServices, config: &Config) -> usize {
+    let wanted = config.heap_kib as usize * 1024;
+    #[repr(C, align(8))]
+    struct MapBuf([u8; 16 * 1024]);
+    let mut buf = MapBuf([0; 16 * 1024]);
+    let Ok(map) = bt.memory_map(&mut buf.0) else {
+        return wanted;
+    };
+    let free: usize = map
+        .entries()
+        .filter(|d| d.ty == MemoryType::CONVENTIONAL)
+        .map(|d| d.page_count as usize * PAGE_SIZE)
+        .sum();
+    wanted.min(free / HEAP_MAX_FRACTION)
+}
+
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    // Safety: `uefi::helpers::init` called in `main` before potential panics
-    if let Some(mut st) = unsafe { uefi_console() } {
-        let _ = st.stdout().clear();
-        let _ =
-            core::fmt::Write::write_str(&mut st.stdout(), "========== KERNEL PANIC ==========\r\n");
-        let _ = core::fmt::Write::write_str(&mut st.stdout(), "A FATAL ERROR OCCURRED\r\n");
-
-        let _ =
-            core::fmt::Write::write_fmt(&mut st.stdout(), format_args!("MESSAGE: {}\r\n", info));
-    } else {
-        vga::clear_screen();
-        vga::set_cursor_position(0, 0);
-        vga::writeln_fmt(format_args!("========== KERNEL PANIC =========="));
-        vga::writeln_fmt(format_args!(" FATAL ERROR OCURRED!"));
-        vga::writeln_fmt(format_args!(" MESSAGE: {}", info));
+    // Safety: `uefi_console` only hands out the table while boot services are up.
+    let mut st = unsafe { uefi_console() };
+    let mut log = match st.as_mut() {
+        Some(st) => {
+            let _ = st.stdout().clear();
+            Log::console(st.stdout())
+        }
+        None => {
+            vga::clear_screen();
+            vga::set_cursor_position(0, 0);
+            shared::log::use_vga(true);
+            Log::headless()
+        }
+    };
+    let _ = writeln!(log, "========== KERNEL PANIC ==========");
+    let _ = writeln!(log, "A FATAL ERROR OCCURRED");
+    let _ = writeln!(log, "MESSAGE: {}", info);
+    let mut frames = [0usize; backtrace::MAX_DEPTH];
+    let depth = backtrace::capture(&mut frames);
+    if depth > 0 {
+        let _ = writeln!(log, "BACKTRACE:");
+        for (i, addr) in frames[..depth].iter().enumerate() {
+            let _ = writeln!(log, "  #{:<2} {:#018x}", i, addr);
+        }
+    }
+    drop(log);
+
+    // Runtime services outlive most other damage, so a reboot usually works
+    // even when little else does. A keypress keeps the screen for inspection.
+    if let Some(st) = st.as_mut() {
+        let _ = writeln!(st.stdout());
+        if countdown(
+            st,
+            "Press any key to stay halted. Rebooting in ",
+            PANIC_REBOOT_SECS,
+        )
+        .is_none()
+        {
+            st.runtime_services()
+                .reset(ResetType::COLD, Status::ABORTED, None);
+        }
+        let _ = writeln!(st.stdout(), "halted");
     }
     loop {}
 }
 
+/// The system table, or `None` before `helpers::init` or after boot services exit.
 unsafe fn uefi_console() -> Option<SystemTable<Boot>> {
+    if !BOOT_CONSOLE.load(Ordering::Acquire) {
+        return None;
+    }
     #[allow(unused_unsafe)]
     {
-        // SAFETY: accessing global set by helpers::init.
-        // if not initialized, this may panic, if it does: the panic handler will double panic and abort
+        // SAFETY: `BOOT_CONSOLE` is only set after helpers::init stored the table.
         let st: SystemTable<Boot> = unsafe { uefi::helpers::system_table() };
         Some(st)
     }
 }
 
+unsafe extern "efiapi" fn boot_services_exited(_event: Event, _context: Option<NonNull<c_void>>) {
+    BOOT_CONSOLE.store(false, Ordering::Release);
+}
+
 #[alloc_error_handler]
 fn alloc_error_handler(layout: alloc::alloc::Layout) -> ! {
     panic!("allocation error: {:?}", layout)
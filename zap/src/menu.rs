@@ -0,0 +1,103 @@
+//! The boot menu, opened by pressing Escape during the countdown.
+//!
+//! Entries come from `\boot.cfg`, one `label=action` line each, where the
+//! action is `mochi` (start the shell) or `shell` (return to the firmware,
+//! which usually offers its UEFI shell). Blank lines, `#` comments and lines
+//! with an unknown action are skipped. Without the file the menu offers both
+//! actions.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use uefi::prelude::*;
+use uefi::proto::console::text::{Key, ScanCode};
+
+pub const PATH: &str = "\\boot.cfg";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Mochi,
+    Firmware,
+}
+
+pub struct Entry {
+    pub label: String,
+    pub action: Action,
+}
+
+/// Reads the menu entries from [`PATH`], falling back to the defaults when
+/// the file is missing or lists nothing usable.
+pub fn load(st: &mut SystemTable<Boot>) -> Vec<Entry> {
+    let entries = match nori::with_boot_fs(st.boot_services(), |fs| nori::read_file(fs, PATH)) {
+        Ok(bytes) => parse(core::str::from_utf8(&bytes).unwrap_or("")),
+        Err(_) => Vec::new(),
+    };
+    if !entries.is_empty() {
+        return entries;
+    }
+    alloc::vec![
+        Entry {
+            label: String::from("Boot mochi"),
+            action: Action::Mochi,
+        },
+        Entry {
+            label: String::from("UEFI shell"),
+            action: Action::Firmware,
+        },
+    ]
+}
+
+fn parse(text: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((label, action)) = line.split_once('=') else {
+            continue;
+        };
+        let action = match action.trim() {
+            "mochi" => Action::Mochi,
+            "shell" => Action::Firmware,
+            _ => continue,
+        };
+        entries.push(Entry {
+            label: String::from(label.trim()),
+            action,
+        });
+    }
+    entries
+}
+
+/// Shows `entries` and lets the user pick one with Up/Down and Enter.
+pub fn choose(st: &mut SystemTable<Boot>, entries: &[Entry]) -> Action {
+    let mut selected = 0;
+    loop {
+        let _ = st.stdout().clear();
+        let _ = writeln!(st.stdout(), "Boot menu (Up/Down to choose, Enter to boot)");
+        let _ = writeln!(st.stdout());
+        for (i, entry) in entries.iter().enumerate() {
+            let marker = if i == selected { '>' } else { ' ' };
+            let _ = writeln!(st.stdout(), " {} {}", marker, entry.label);
+        }
+        loop {
+            let key = match st.stdin().read_key() {
+                Ok(Some(key)) => key,
+                _ => {
+                    st.boot_services().stall(10_000);
+                    continue;
+                }
+            };
+            match key {
+                Key::Special(ScanCode::UP) if selected > 0 => selected -= 1,
+                Key::Special(ScanCode::DOWN) if selected + 1 < entries.len() => selected += 1,
+                Key::Printable(c) if matches!(char::from(c), '\r' | '\n') => {
+                    return entries[selected].action;
+                }
+                _ => continue,
+            }
+            break;
+        }
+    }
+}